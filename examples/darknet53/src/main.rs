@@ -8,6 +8,7 @@ extern crate neural_net;
 
 use std::error::Error;
 use std::io::{BufRead, Read};
+use std::rc::Rc;
 
 use byteorder::{LittleEndian, ReadBytesExt};
 use ndarray::Dimension;
@@ -40,6 +41,60 @@ impl<R: Read> Weights<R> {
     }
 }
 
+// DarknetConv2D is a convolution whose kernel variable keeps darknet's layout of (out_channels,
+// in_channels, height, width). It's permuted to the layout conv2d expects in the graph.
+struct DarknetConv2D {
+    kernel: ndarray::ArrayD<f32>,
+    bias: Option<ndarray::ArrayD<f32>>,
+    stride: usize,
+}
+
+struct DarknetConv2DInstance {
+    kernel: algebra::Expr,
+    bias: Option<algebra::Expr>,
+    stride: usize,
+    variables: Vec<neural_net::LayerVariable>,
+}
+
+impl neural_net::Layer for DarknetConv2D {
+    fn init(self: Box<Self>, namespace: &str, _input_shape: &ndarray::IxDyn) -> Box<dyn neural_net::LayerInstance> {
+        let mut variables = Vec::new();
+        let mut variable = |name: &str, init: ndarray::ArrayD<f32>| {
+            let v = neural_net::LayerVariable{
+                name: format!("{}.{}", namespace, name),
+                value: Rc::new(algebra::VariableValue::new(init)),
+                constraint: None,
+                trainable: true,
+            };
+            variables.push(v.clone());
+            algebra::v(v.name, v.value)
+        };
+        let kernel = variable("w", self.kernel);
+        let bias = self.bias.map(|bias| variable("b", bias));
+        Box::new(DarknetConv2DInstance{
+            kernel: kernel,
+            bias: bias,
+            stride: self.stride,
+            variables: variables,
+        })
+    }
+}
+
+impl neural_net::LayerInstance for DarknetConv2DInstance {
+    fn expression(&self, input: algebra::Expr) -> algebra::Expr {
+        let kernel = algebra::transpose_axes(self.kernel.clone(), vec![2, 3, 1, 0]);
+        let result = algebra::conv2d(input, kernel, self.stride, algebra::conv2d::Padding::Same);
+        match &self.bias {
+            Some(bias) => result.clone() + algebra::broadcast_to(bias.clone(), result.shape()),
+            None => result,
+        }
+    }
+
+    fn variables(&self) -> &[neural_net::LayerVariable] {
+        self.variables.as_slice()
+    }
+}
+
 fn darknet_convolutional<R, A>(weights: &mut Weights<R>, filters: usize, size: usize, stride: usize, batch_normalize: bool, activation: A, channels_in: usize) -> Result<Box<layers::Sequential>, Box<dyn Error>>
     where R: Read,
           A: Fn(algebra::Expr) -> algebra::Expr + 'static,
@@ -57,15 +112,13 @@ fn darknet_convolutional<R, A>(weights: &mut Weights<R>, filters: usize, size: u
         false => None,
     };
     let mut ret = neural_net::layers::Sequential{
-        layers: vec![Box::new(layers::Conv2D{
-            activation: activations::linear,
-            bias_initializer: neural_net::initializers::copy(bias.clone()),
-            kernel_initializer: neural_net::initializers::copy(weights.read(ndarray::Ix4(filters, channels_in, size, size))?.permuted_axes(vec![2, 3, 1, 0])),
-            filters: filters,
-            kernel_size: ndarray::Ix2(size, size),
-            padding: algebra::conv2d::Padding::Same,
+        layers: vec![Box::new(DarknetConv2D{
+            kernel: weights.read(ndarray::Ix4(filters, channels_in, size, size))?,
+            bias: match batch_normalize {
+                true => None,
+                false => Some(bias),
+            },
             stride: stride,
-            use_bias: !batch_normalize,
         })],
     };
     if let Some(l) = batch_normalization {
@@ -138,7 +191,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     model.add_layer(darknet53(weights)?)?;
 
     info!("compiling model");
-    let mut model = model.compile_for_inference();
+    let mut model = model.compile_for_inference()?;
 
     info!("loading image");
     let image = image::open("dog.jpg")?.to_rgb();
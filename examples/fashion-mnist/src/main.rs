@@ -24,11 +24,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         output_size: 128,
         activation: neural_net::activations::relu,
         kernel_initializer: neural_net::initializers::glorot_uniform,
+        kernel_regularizer: None,
+        bias_regularizer: None,
+        kernel_constraint: None,
     })?;
     model.add_layer(neural_net::layers::Dense{
         output_size: 10,
         activation: neural_net::activations::softmax,
         kernel_initializer: neural_net::initializers::glorot_uniform,
+        kernel_regularizer: None,
+        bias_regularizer: None,
+        kernel_constraint: None,
     })?;
 
     info!("loading training data");
@@ -37,10 +43,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut training_dataset = neural_net::datasets::MNIST::new(training_images, training_labels)?.to_one_hot(CLASS_NAMES.len());
 
     info!("compiling model");
-    let mut model = model.compile_for_training(training_dataset.target_shape(), neural_net::losses::categorical_cross_entropy);
+    let mut model = model.compile_for_training(training_dataset.target_shape(), neural_net::losses::categorical_cross_entropy)?;
+    model.set_metrics(vec![Box::new(neural_net::metrics::Accuracy::new())]);
 
     info!("fitting model");
-    model.fit(&mut training_dataset, 0.003, 5)?;
+    model.fit(&mut training_dataset, 0.003, 5, None, &mut [])?;
 
     Ok(())
 }
@@ -1,23 +1,99 @@
 use super::algebra;
 
+use algebra::cmp::Op;
+
 pub fn linear(input: algebra::Expr) -> algebra::Expr {
     input
 }
 
 pub fn relu(input: algebra::Expr) -> algebra::Expr {
-    input.max(algebra::expr(0.0))
+    algebra::relu(input)
 }
 
 pub fn leaky_relu(alpha: f32) -> impl Fn(algebra::Expr) -> algebra::Expr {
+    move |input| algebra::leaky_relu(input, alpha)
+}
+
+pub fn sigmoid(input: algebra::Expr) -> algebra::Expr {
+    input.sigmoid()
+}
+
+pub fn tanh(input: algebra::Expr) -> algebra::Expr {
+    input.tanh()
+}
+
+pub fn elu(alpha: f32) -> impl Fn(algebra::Expr) -> algebra::Expr {
     move |input| {
+        // The exponential is only evaluated for non-positive inputs so that it can't overflow.
         algebra::ternary(
             algebra::cmp(input.clone(), algebra::cmp::Op::Less, algebra::expr(0.0)),
-            input.clone() * algebra::expr(alpha),
+            alpha * input.clip(f32::NEG_INFINITY, 0.0).expm1(),
             input,
         )
     }
 }
 
+// These are the constants used by "Self-Normalizing Neural Networks" (Klambauer et al.).
+const SELU_ALPHA: f32 = 1.673_263_2;
+const SELU_SCALE: f32 = 1.050_701;
+
+pub fn selu(input: algebra::Expr) -> algebra::Expr {
+    SELU_SCALE * elu(SELU_ALPHA)(input)
+}
+
+pub fn gelu(input: algebra::Expr) -> algebra::Expr {
+    0.5 * input.clone() * (1.0 + (input * std::f32::consts::FRAC_1_SQRT_2).erf())
+}
+
+// gelu_tanh is the tanh approximation of gelu used by BERT and GPT-2.
+pub fn gelu_tanh(input: algebra::Expr) -> algebra::Expr {
+    let c = (2.0 / std::f32::consts::PI).sqrt();
+    let inner = c * (input.clone() + 0.044_715 * input.pow(3.0));
+    0.5 * input * (1.0 + inner.tanh())
+}
+
+pub fn swish(input: algebra::Expr) -> algebra::Expr {
+    input.clone() * input.sigmoid()
+}
+
+pub fn silu(input: algebra::Expr) -> algebra::Expr {
+    swish(input)
+}
+
+pub fn softplus(input: algebra::Expr) -> algebra::Expr {
+    input.softplus()
+}
+
+pub fn softsign(input: algebra::Expr) -> algebra::Expr {
+    input.clone() / (1.0 + input.abs())
+}
+
+pub fn mish(input: algebra::Expr) -> algebra::Expr {
+    input.clone() * input.softplus().tanh()
+}
+
+// Unlike clip, which passes the gradient through at its bounds, relu6 has a gradient of zero at 0
+// and 6, as in TensorFlow.
+pub fn relu6(input: algebra::Expr) -> algebra::Expr {
+    let at_most = |bound: f32| algebra::cmp(input.clone(), Op::LessOrEqual, algebra::expr(bound));
+    let at_least =
+        |bound: f32| algebra::cmp(input.clone(), Op::GreaterOrEqual, algebra::expr(bound));
+    algebra::ternary(
+        at_most(0.0),
+        algebra::expr(0.0),
+        algebra::ternary(at_least(6.0), algebra::expr(6.0), input.clone()),
+    )
+}
+
+// hard_sigmoid is the piecewise linear approximation used by MobileNetV3: relu6(x + 3) / 6.
+pub fn hard_sigmoid(input: algebra::Expr) -> algebra::Expr {
+    relu6(input + 3.0) / 6.0
+}
+
+pub fn hard_swish(input: algebra::Expr) -> algebra::Expr {
+    input.clone() * hard_sigmoid(input)
+}
+
 pub fn softmax(input: algebra::Expr) -> algebra::Expr {
     input.softmax()
 }
@@ -53,4 +129,144 @@ mod tests {
             ndarray::arr1(&[0.0, 0.0, 0.0]).into_dyn()
         );
     }
+
+    fn check<F: Fn(algebra::Expr) -> algebra::Expr>(f: F, values: &[f32], gradients: &[f32]) {
+        let x = algebra::v(
+            "x",
+            Rc::new(algebra::VariableValue::new(ndarray::arr1(&[
+                -3.0, -1.0, 0.0, 0.5, 2.0, 7.0,
+            ]))),
+        );
+        let y = f(x);
+        assert!(
+            y.eval().all_close(&ndarray::arr1(values).into_dyn(), 1e-5),
+            "{} != {:?}",
+            y.eval(),
+            values
+        );
+        let gradient = y.gradient("x").eval();
+        assert!(
+            gradient.all_close(&ndarray::arr1(gradients).into_dyn(), 1e-4),
+            "{} != {:?}",
+            gradient,
+            gradients
+        );
+    }
+
+    #[test]
+    fn test_activations() {
+        // Expected values are from the definitions in double precision.
+        check(
+            sigmoid,
+            &[0.04742587, 0.2689414, 0.5, 0.6224593, 0.8807971, 0.9990889],
+            &[
+                0.04517666,
+                0.1966119,
+                0.25,
+                0.2350037,
+                0.1049936,
+                0.0009102212,
+            ],
+        );
+        check(
+            tanh,
+            &[-0.9950548, -0.7615942, 0.0, 0.4621172, 0.9640276, 0.9999983],
+            &[
+                0.009866037,
+                0.4199743,
+                1.0,
+                0.7864477,
+                0.07065082,
+                3.326117e-06,
+            ],
+        );
+        check(
+            elu(1.0),
+            &[-0.9502129, -0.6321206, 0.0, 0.5, 2.0, 7.0],
+            &[0.04978707, 0.3678794, 1.0, 1.0, 1.0, 1.0],
+        );
+        check(
+            selu,
+            &[-1.670569, -1.111331, 0.0, 0.5253505, 2.101402, 7.354907],
+            &[
+                0.08753061, 0.6467686, 1.050701, 1.050701, 1.050701, 1.050701,
+            ],
+        );
+        check(
+            gelu,
+            &[-0.004049694, -0.1586553, 0.0, 0.3457312, 1.9545, 7.0],
+            &[-0.01194565, -0.08331547, 0.5, 0.8674951, 1.085232, 1.0],
+        );
+        check(
+            gelu_tanh,
+            &[-0.003637392, -0.158808, 0.0, 0.345714, 1.954598, 7.0],
+            &[-0.01158417, -0.08296408, 0.5, 0.8673699, 1.086099, 1.0],
+        );
+        check(
+            swish,
+            &[-0.1422776, -0.2689414, 0.0, 0.3112297, 1.761594, 6.993623],
+            &[-0.08810411, 0.07232949, 0.5, 0.7399612, 1.090784, 1.00546],
+        );
+        check(
+            softplus,
+            &[
+                0.04858735,
+                0.3132617,
+                std::f32::consts::LN_2,
+                0.974077,
+                2.126928,
+                7.000911,
+            ],
+            &[0.04742587, 0.2689414, 0.5, 0.6224593, 0.8807971, 0.9990889],
+        );
+        check(
+            softsign,
+            &[-0.75, -0.5, 0.0, 0.3333333, 0.6666667, 0.875],
+            &[0.0625, 0.25, 1.0, 0.4444444, 0.1111111, 0.015625],
+        );
+        check(
+            mish,
+            &[-0.1456475, -0.3034015, 0.0, 0.3752452, 1.943959, 6.999988],
+            &[-0.09339311, 0.05921676, 0.6, 0.8864244, 1.069318, 1.000022],
+        );
+        check(
+            relu6,
+            &[0.0, 0.0, 0.0, 0.5, 2.0, 6.0],
+            &[0.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+        );
+        check(
+            hard_sigmoid,
+            &[0.0, 0.3333333, 0.5, 0.5833333, 0.8333333, 1.0],
+            &[0.0, 0.1666667, 0.1666667, 0.1666667, 0.1666667, 0.0],
+        );
+        check(
+            hard_swish,
+            &[0.0, -0.3333333, 0.0, 0.2916667, 1.666667, 7.0],
+            &[0.0, 0.1666667, 0.5, 0.6666667, 1.166667, 1.0],
+        );
+    }
+
+    #[test]
+    fn test_gradients_at_bounds() {
+        let x = algebra::v(
+            "x",
+            Rc::new(algebra::VariableValue::new(ndarray::arr1(&[
+                -3.0, 0.0, 3.0, 6.0,
+            ]))),
+        );
+
+        // tf.gradients(tf.nn.relu6(x), x), etc.
+        assert_eq!(
+            relu6(x.clone()).gradient("x").eval(),
+            ndarray::arr1(&[0.0, 0.0, 1.0, 0.0]).into_dyn()
+        );
+        assert_eq!(
+            hard_sigmoid(x.clone()).gradient("x").eval(),
+            ndarray::arr1(&[0.0, 1.0 / 6.0, 0.0, 0.0]).into_dyn()
+        );
+        assert_eq!(
+            hard_swish(x).gradient("x").eval(),
+            ndarray::arr1(&[0.0, 0.5, 1.0, 1.0]).into_dyn()
+        );
+    }
 }
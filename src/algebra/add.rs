@@ -64,6 +64,15 @@ impl ExprImpl for Add {
         vec![Some(output.clone()), Some(output.clone())]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        match (&tangents[0], &tangents[1]) {
+            (Some(left), Some(right)) => Some(left.clone() + right.clone()),
+            (Some(left), None) => Some(left.clone()),
+            (None, Some(right)) => Some(right.clone()),
+            (None, None) => None,
+        }
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.left, &self.right]
     }
@@ -85,6 +94,16 @@ impl<T: Into<Expr>> std::ops::Add<T> for Expr {
     }
 }
 
+impl std::ops::Add<Expr> for f32 {
+    type Output = Expr;
+    fn add(self, rhs: Expr) -> Expr {
+        Expr::new(Add {
+            left: super::expr(self),
+            right: rhs,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::*;
@@ -135,4 +154,23 @@ mod tests {
             ndarray::arr1(&[1.0, 1.0, 1.0]).into_dyn()
         );
     }
+
+    #[test]
+    fn test_jvp() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr1(&[0.0, 1.0, 2.0])));
+        let y_value = Rc::new(VariableValue::new(ndarray::arr0(5.0)));
+        let x = v("x", x_value.clone());
+        let y = v("y", y_value.clone());
+        let mut tangents = HashMap::new();
+        tangents.insert(x_value.id(), expr(ndarray::arr1(&[1.0, 0.0, 0.0])));
+        assert_eq!(
+            (x.clone() + y.clone()).jvp(&tangents).eval(),
+            ndarray::arr1(&[1.0, 0.0, 0.0]).into_dyn()
+        );
+        tangents.insert(y_value.id(), expr(2.0));
+        assert_eq!(
+            (x + y).jvp(&tangents).eval(),
+            ndarray::arr1(&[3.0, 2.0, 2.0]).into_dyn()
+        );
+    }
 }
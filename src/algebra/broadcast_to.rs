@@ -53,6 +53,12 @@ impl ExprImpl for BroadcastTo {
         )]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| broadcast_to(t.clone(), self.shape.clone()))
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
@@ -0,0 +1,88 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Clip limits each element to the range [min, max]. The gradient passes through unchanged for
+// elements within the range and is zero for elements that were clipped.
+pub struct Clip {
+    pub expr: Expr,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Clip {
+    fn mask(&self) -> Expr {
+        super::cmp(
+            self.expr.clone(),
+            super::cmp::Op::GreaterOrEqual,
+            super::expr(self.min),
+        ) * super::cmp(
+            self.expr.clone(),
+            super::cmp::Op::LessOrEqual,
+            super::expr(self.max),
+        )
+    }
+}
+
+impl ExprImpl for Clip {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| v.max(self.min).min(self.max))
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().clip(self.min, self.max)
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.mask())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.mask())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Clip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "clip({}, {}, {})", self.expr, self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[-2.0, 0.5, 3.0]))),
+        );
+        let y = x.clip(0.0, 1.0);
+        assert_eq!(y.eval(), ndarray::arr1(&[0.0, 0.5, 1.0]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[0.0, 1.0, 0.0]).into_dyn()
+        );
+    }
+}
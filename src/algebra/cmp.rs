@@ -101,7 +101,13 @@ impl ExprImpl for Cmp {
         _output: Expr,
         _gradients: &mut super::Gradients,
     ) -> Vec<Option<Expr>> {
-        panic!("gradients are not supported for comparisons")
+        // Comparisons are piecewise constant, so their gradients are zero almost everywhere.
+        vec![None, None]
+    }
+
+    fn jvp_inputs(&self, _tangents: &[Option<Expr>]) -> Option<Expr> {
+        // Comparisons are piecewise constant, so their outputs never change with their inputs.
+        None
     }
 
     fn inputs(&self) -> Vec<&Expr> {
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+// Concat joins expressions along an existing axis. All other axes must have the same lengths.
+pub struct Concat {
+    pub exprs: Vec<Expr>,
+    pub axis: usize,
+}
+
+impl ExprImpl for Concat {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let views: Vec<_> = inputs.iter().map(|input| input.view()).collect();
+        ndarray::stack(ndarray::Axis(self.axis), &views).unwrap()
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let mut shape = self.exprs[0].shape();
+        for expr in self.exprs.iter().skip(1) {
+            let other = expr.shape();
+            let mut expected = shape.clone();
+            expected[self.axis] = other[self.axis];
+            if other != expected {
+                panic!(
+                    "incompatible shapes for concat on axis {}. got {:?}, expected {:?}",
+                    self.axis, other, expected
+                );
+            }
+            shape[self.axis] += other[self.axis];
+        }
+        shape
+    }
+
+    fn is_constant(&self) -> bool {
+        self.exprs.iter().all(|expr| expr.is_constant())
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            concat(
+                self.exprs
+                    .iter()
+                    .map(|expr| expr.propagate_constants())
+                    .collect(),
+                self.axis,
+            )
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        let sizes: Vec<_> = self
+            .exprs
+            .iter()
+            .map(|expr| expr.shape()[self.axis])
+            .collect();
+        super::split(output, self.axis, &sizes)
+            .into_iter()
+            .map(Some)
+            .collect()
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        Some(concat(
+            tangents
+                .iter()
+                .zip(self.exprs.iter())
+                .map(|(t, expr)| match t {
+                    Some(t) => t.clone(),
+                    None => super::expr(ndarray::Array::zeros(expr.shape())),
+                })
+                .collect(),
+            self.axis,
+        ))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        self.exprs.iter().collect()
+    }
+}
+
+impl fmt::Display for Concat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "concat({:?}, {})", self.exprs, self.axis)
+    }
+}
+
+pub fn concat(exprs: Vec<Expr>, axis: usize) -> Expr {
+    if exprs.is_empty() {
+        panic!("concat requires at least one expression");
+    }
+    Expr::new(Concat { exprs, axis })
+}
+
+// Joins expressions of identical shape along a new axis.
+pub fn stack(exprs: Vec<Expr>, axis: usize) -> Expr {
+    concat(
+        exprs
+            .into_iter()
+            .map(|expr| {
+                let mut shape = expr.shape().slice().to_vec();
+                shape.insert(axis, 1);
+                expr.reshape(ndarray::IxDyn(&shape))
+            })
+            .collect(),
+        axis,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[0.0, 1.0]]))),
+        );
+        let y = v(
+            "y",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[2.0, 3.0], [4.0, 5.0]]))),
+        );
+        let z = concat(vec![x.clone(), y.clone()], 0);
+        assert_eq!(
+            z.eval(),
+            ndarray::arr2(&[[0.0, 1.0], [2.0, 3.0], [4.0, 5.0]]).into_dyn()
+        );
+        let weighted = z * expr(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]));
+        assert_eq!(
+            weighted.gradient("x").eval(),
+            ndarray::arr2(&[[1.0, 2.0]]).into_dyn()
+        );
+        assert_eq!(
+            weighted.gradient("y").eval(),
+            ndarray::arr2(&[[3.0, 4.0], [5.0, 6.0]]).into_dyn()
+        );
+
+        let x = expr(ndarray::arr1(&[0.0, 1.0]));
+        let y = expr(ndarray::arr1(&[2.0, 3.0]));
+        assert_eq!(
+            stack(vec![x.clone(), y.clone()], 0).eval(),
+            ndarray::arr2(&[[0.0, 1.0], [2.0, 3.0]]).into_dyn()
+        );
+        assert_eq!(
+            stack(vec![x, y], 1).eval(),
+            ndarray::arr2(&[[0.0, 2.0], [1.0, 3.0]]).into_dyn()
+        );
+    }
+}
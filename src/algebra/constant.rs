@@ -31,6 +31,10 @@ impl ExprImpl for Constant {
         vec![]
     }
 
+    fn jvp_inputs(&self, _tangents: &[Option<Expr>]) -> Option<Expr> {
+        None
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![]
     }
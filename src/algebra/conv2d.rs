@@ -163,6 +163,29 @@ impl ExprImpl for Conv2D {
         panic!("Conv2D gradients have not been implemented.")
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        let input = tangents[0].as_ref().map(|input| {
+            conv2d(
+                input.clone(),
+                self.kernel.clone(),
+                self.stride,
+                self.padding,
+            )
+        });
+        let kernel = tangents[1].as_ref().map(|kernel| {
+            conv2d(
+                self.input.clone(),
+                kernel.clone(),
+                self.stride,
+                self.padding,
+            )
+        });
+        match (input, kernel) {
+            (Some(input), Some(kernel)) => Some(input + kernel),
+            (input, kernel) => input.or(kernel),
+        }
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.input, &self.kernel]
     }
@@ -237,4 +260,36 @@ mod tests {
             .into_dyn()
         );
     }
+
+    #[test]
+    fn test_jvp() {
+        let img = expr(ndarray::arr3(&[
+            [[0.0, 1.0], [1.0, 2.0]],
+            [[2.0, 3.0], [3.0, 4.0]],
+        ]));
+        let kernel_value = Rc::new(VariableValue::new(ndarray::Array::zeros((1, 2, 2, 3))));
+        let kernel = v("k", kernel_value.clone());
+        let mut kernel_tangent = ndarray::Array::zeros((1, 2, 2, 3));
+        kernel_tangent
+            .slice_mut(s![0, .., .., ..])
+            .assign(&ndarray::arr3(&[
+                [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+                [[11.0, 12.0, 13.0], [14.0, 15.0, 16.0]],
+            ]));
+
+        // The convolution is linear in the kernel, so the product is just the convolution with the
+        // tangent.
+        let mut tangents = HashMap::new();
+        tangents.insert(kernel_value.id(), expr(kernel_tangent));
+        assert_eq!(
+            conv2d(img.clone(), kernel, 1, Padding::Same)
+                .jvp(&tangents)
+                .eval(),
+            ndarray::arr3(&[
+                [[43., 47., 51.], [9., 12., 15.]],
+                [[103., 115., 127.], [19., 26., 33.]]
+            ])
+            .into_dyn()
+        );
+    }
 }
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::rc::Rc;
+
+use super::{Expr, ExprImpl};
+
+// CustomGradient evaluates to its input, but replaces the gradient that's propagated back to it.
+// The gradient function is given the gradient of the output and returns the gradient of the
+// input. Forward-mode differentiation ignores the gradient function and passes tangents through
+// unchanged.
+pub struct CustomGradient {
+    pub expr: Expr,
+    pub gradient: Rc<dyn Fn(Expr) -> Expr>,
+}
+
+impl ExprImpl for CustomGradient {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].clone()
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            Expr::new(CustomGradient {
+                expr: self.expr.propagate_constants(),
+                gradient: self.gradient.clone(),
+            })
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some((self.gradient)(output))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].clone()
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for CustomGradient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "custom_gradient({})", self.expr)
+    }
+}
+
+pub fn custom_gradient<V, F>(expr: V, gradient: F) -> Expr
+where
+    V: Into<Expr>,
+    F: Fn(Expr) -> Expr + 'static,
+{
+    Expr::new(CustomGradient {
+        expr: expr.into(),
+        gradient: Rc::new(gradient),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::graph;
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr1(&[1.0, 2.0])));
+        let x = v("x", x_value.clone());
+
+        // gradient reversal
+        let y = custom_gradient(x.square(), |g| -1.0 * g).sum();
+        assert_eq!(y.eval(), ndarray::arr0(5.0).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[-2.0, -4.0]).into_dyn()
+        );
+
+        let mut graph = graph::Graph::new();
+        let gradient = graph.add(y.gradients_wrt(&[x])[0].clone());
+        x_value.set(ndarray::arr1(&[3.0, 4.0]));
+        graph.eval();
+        assert_eq!(
+            graph.node_output(gradient),
+            &ndarray::arr1(&[-6.0, -8.0]).into_dyn()
+        );
+    }
+}
@@ -61,6 +61,19 @@ impl ExprImpl for Div {
         ]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        let num = tangents[0]
+            .as_ref()
+            .map(|num| num.clone() / self.den.clone());
+        let den = tangents[1]
+            .as_ref()
+            .map(|den| den.clone() * (-1.0 * self.num.clone() / self.den.square()));
+        match (num, den) {
+            (Some(num), Some(den)) => Some(num + den),
+            (num, den) => num.or(den),
+        }
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.num, &self.den]
     }
@@ -142,4 +155,20 @@ mod tests {
             ndarray::arr1(&[0.0, 0.0, 0.0]).into_dyn()
         );
     }
+
+    #[test]
+    fn test_jvp() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[0.0, 1.0, 2.0]))),
+        );
+        let y_value = Rc::new(VariableValue::new(ndarray::arr1(&[1.0, 1.0, 5.0])));
+        let y = v("y", y_value.clone());
+        let mut tangents = HashMap::new();
+        tangents.insert(y_value.id(), expr(ndarray::arr1(&[1.0, 1.0, 1.0])));
+        assert_eq!(
+            (x / y).jvp(&tangents).eval(),
+            ndarray::arr1(&[0.0, -1.0, -0.08]).into_dyn()
+        );
+    }
 }
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+// Einsum evaluates an Einstein summation. Each operand and the output are described by a list of
+// single-character labels, one per axis. The output is the product of the operands, summed over
+// every label that doesn't appear in the output. Repeating a label within an operand takes a
+// diagonal. Repeating one in the output writes a diagonal, leaving everything else zero.
+pub struct Einsum {
+    pub exprs: Vec<Expr>,
+    pub input_labels: Vec<Vec<char>>,
+    pub output_labels: Vec<char>,
+    // The length of each label's axis. This must include labels that only appear in the output.
+    pub sizes: HashMap<char, usize>,
+}
+
+impl Einsum {
+    fn spec(&self) -> String {
+        let inputs: Vec<String> = self
+            .input_labels
+            .iter()
+            .map(|labels| labels.iter().collect())
+            .collect();
+        format!(
+            "{}->{}",
+            inputs.join(","),
+            self.output_labels.iter().collect::<String>()
+        )
+    }
+
+    fn with_exprs(
+        &self,
+        exprs: Vec<Expr>,
+        input_labels: Vec<Vec<char>>,
+        output_labels: Vec<char>,
+    ) -> Expr {
+        Expr::new(Einsum {
+            exprs,
+            input_labels,
+            output_labels,
+            sizes: self.sizes.clone(),
+        })
+    }
+}
+
+// An operand or intermediate result during evaluation, with one label per axis.
+struct Operand {
+    array: ndarray::ArrayD<f32>,
+    labels: Vec<char>,
+}
+
+impl Operand {
+    fn size(&self, label: char) -> usize {
+        self.array.shape()[self.labels.iter().position(|&l| l == label).unwrap()]
+    }
+
+    // Takes the diagonal for each label that's repeated, so that every label is unique.
+    fn take_diagonals(self) -> Operand {
+        let mut labels: Vec<char> = Vec::new();
+        for &label in self.labels.iter() {
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        if labels.len() == self.labels.len() {
+            return self;
+        }
+        let axes: Vec<usize> = self
+            .labels
+            .iter()
+            .map(|l| labels.iter().position(|m| m == l).unwrap())
+            .collect();
+        let shape: Vec<usize> = labels.iter().map(|&l| self.size(l)).collect();
+        let array = ndarray::Array::from_shape_fn(ndarray::IxDyn(&shape), |index| {
+            let index: Vec<usize> = axes.iter().map(|&axis| index[axis]).collect();
+            self.array[ndarray::IxDyn(&index)]
+        });
+        Operand { array, labels }
+    }
+
+    // Sums over each label that isn't needed.
+    fn sum_unneeded<F: Fn(char) -> bool>(mut self, needed: F) -> Operand {
+        for axis in (0..self.labels.len()).rev() {
+            if !needed(self.labels[axis]) {
+                self.array = self.array.sum_axis(ndarray::Axis(axis));
+                self.labels.remove(axis);
+            }
+        }
+        self
+    }
+
+    // Permutes the axes into the order of the given labels, then flattens them into a batch of
+    // matrices. The labels are split into batch, row, and column labels by the given counts.
+    fn to_matrices(&self, labels: &[char], batch: usize, rows: usize) -> ndarray::Array3<f32> {
+        let axes: Vec<usize> = labels
+            .iter()
+            .map(|l| self.labels.iter().position(|m| m == l).unwrap())
+            .collect();
+        let permuted = self.array.view().permuted_axes(ndarray::IxDyn(&axes));
+        let shape = permuted.shape();
+        let shape = (
+            shape[..batch].iter().product(),
+            shape[batch..batch + rows].iter().product(),
+            shape[batch + rows..].iter().product(),
+        );
+        ndarray::Array::from_shape_vec(shape, permuted.iter().cloned().collect()).unwrap()
+    }
+
+    // Multiplies two operands, summing over the labels they share that aren't needed afterwards.
+    // This is a batched matrix multiplication, batched over the shared labels that are needed.
+    fn contract<F: Fn(char) -> bool>(&self, other: &Operand, needed: F) -> Operand {
+        let (mut batch, mut rows, mut contracted) = (Vec::new(), Vec::new(), Vec::new());
+        for &label in self.labels.iter() {
+            if !other.labels.contains(&label) {
+                rows.push(label);
+            } else if needed(label) {
+                batch.push(label);
+            } else {
+                contracted.push(label);
+            }
+        }
+        let cols: Vec<char> = other
+            .labels
+            .iter()
+            .cloned()
+            .filter(|l| !self.labels.contains(l))
+            .collect();
+
+        let a = self.to_matrices(
+            &[&batch[..], &rows[..], &contracted[..]].concat(),
+            batch.len(),
+            rows.len(),
+        );
+        let b = other.to_matrices(
+            &[&batch[..], &contracted[..], &cols[..]].concat(),
+            batch.len(),
+            contracted.len(),
+        );
+        let labels = [&batch[..], &rows[..], &cols[..]].concat();
+        let shape: Vec<usize> = batch
+            .iter()
+            .chain(rows.iter())
+            .map(|&l| self.size(l))
+            .chain(cols.iter().map(|&l| other.size(l)))
+            .collect();
+        Operand {
+            array: super::matmul::mat_mul_batches(a.view(), b.view())
+                .into_shape(shape)
+                .unwrap(),
+            labels,
+        }
+    }
+}
+
+impl ExprImpl for Einsum {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let shape = self.shape();
+        if self.sizes.values().any(|&size| size == 0) {
+            return ndarray::Array::zeros(shape);
+        }
+
+        // Visiting every combination of label values would take far too long, so the operands
+        // are contracted one pair at a time instead. Each label is summed over as soon as nothing
+        // after it needs it.
+        let mut operands = inputs
+            .iter()
+            .zip(self.input_labels.iter())
+            .map(|(array, labels)| {
+                Operand {
+                    array: array.clone(),
+                    labels: labels.clone(),
+                }
+                .take_diagonals()
+            })
+            .collect::<Vec<_>>()
+            .into_iter();
+        let needed_after = |rest: &std::vec::IntoIter<Operand>, label: char| {
+            self.output_labels.contains(&label)
+                || rest.as_slice().iter().any(|o| o.labels.contains(&label))
+        };
+        let mut result = operands.next().unwrap();
+        result = result.sum_unneeded(|l| needed_after(&operands, l));
+        while let Some(other) = operands.next() {
+            let other =
+                other.sum_unneeded(|l| needed_after(&operands, l) || result.labels.contains(&l));
+            result = result.contract(&other, |l| needed_after(&operands, l));
+        }
+
+        // Now each label in the result appears in the output. If the output labels are just a
+        // permutation of them, that's all that's left to do.
+        let mut output_labels = self.output_labels.clone();
+        output_labels.sort();
+        output_labels.dedup();
+        if output_labels.len() == self.output_labels.len()
+            && output_labels.len() == result.labels.len()
+        {
+            return result
+                .to_matrices(&self.output_labels, 0, 0)
+                .into_shape(shape)
+                .unwrap();
+        }
+
+        // Otherwise labels that only appear in the output are broadcasted, and repeated labels
+        // write a diagonal.
+        let first_axes: Vec<usize> = self
+            .output_labels
+            .iter()
+            .map(|l| self.output_labels.iter().position(|m| m == l).unwrap())
+            .collect();
+        let result_axes: Vec<usize> = result
+            .labels
+            .iter()
+            .map(|l| self.output_labels.iter().position(|m| m == l).unwrap())
+            .collect();
+        ndarray::Array::from_shape_fn(shape, |index| {
+            if (0..first_axes.len()).any(|axis| index[axis] != index[first_axes[axis]]) {
+                return 0.0;
+            }
+            let index: Vec<usize> = result_axes.iter().map(|&axis| index[axis]).collect();
+            result.array[ndarray::IxDyn(&index)]
+        })
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        ndarray::IxDyn(
+            &self
+                .output_labels
+                .iter()
+                .map(|l| self.sizes[l])
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn is_constant(&self) -> bool {
+        self.exprs.iter().all(|expr| expr.is_constant())
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.with_exprs(
+                self.exprs.iter().map(|e| e.propagate_constants()).collect(),
+                self.input_labels.clone(),
+                self.output_labels.clone(),
+            )
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        // The gradient for each operand is the summation of the output gradient with every other
+        // operand, producing the operand's labels.
+        (0..self.exprs.len())
+            .map(|i| {
+                let mut exprs = vec![output.clone()];
+                let mut input_labels = vec![self.output_labels.clone()];
+                for j in 0..self.exprs.len() {
+                    if j != i {
+                        exprs.push(self.exprs[j].clone());
+                        input_labels.push(self.input_labels[j].clone());
+                    }
+                }
+                Some(self.with_exprs(exprs, input_labels, self.input_labels[i].clone()))
+            })
+            .collect()
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        let mut result: Option<Expr> = None;
+        for (i, t) in tangents.iter().enumerate() {
+            if let Some(t) = t {
+                let mut exprs = self.exprs.clone();
+                exprs[i] = t.clone();
+                let term =
+                    self.with_exprs(exprs, self.input_labels.clone(), self.output_labels.clone());
+                result = Some(match result {
+                    Some(result) => result + term,
+                    None => term,
+                });
+            }
+        }
+        result
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        self.exprs.iter().collect()
+    }
+}
+
+impl fmt::Display for Einsum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "einsum(\"{}\", {:?})", self.spec(), self.exprs)
+    }
+}
+
+// Evaluates an Einstein summation described by a numpy-style spec such as "ij,jk->ik". If the
+// output labels are omitted (e.g. "ij,jk"), the output consists of the labels that appear exactly
+// once, in alphabetical order. Ellipses aren't supported.
+pub fn einsum(spec: &str, exprs: Vec<Expr>) -> Expr {
+    let spec: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+    let (inputs, output) = match spec.find("->") {
+        Some(i) => (&spec[..i], Some(&spec[i + 2..])),
+        None => (spec.as_str(), None),
+    };
+    let input_labels: Vec<Vec<char>> = inputs.split(',').map(|s| s.chars().collect()).collect();
+    if input_labels.len() != exprs.len() {
+        panic!(
+            "einsum spec {} has {} operands, but got {}",
+            spec,
+            input_labels.len(),
+            exprs.len()
+        );
+    }
+
+    let mut sizes = HashMap::new();
+    let mut counts = HashMap::new();
+    for (labels, expr) in input_labels.iter().zip(exprs.iter()) {
+        let shape = expr.shape();
+        if labels.len() != shape.ndim() {
+            panic!(
+                "einsum labels {:?} don't match operand shape {:?}",
+                labels, shape
+            );
+        }
+        for (&label, &size) in labels.iter().zip(shape.slice()) {
+            if *sizes.entry(label).or_insert(size) != size {
+                panic!("inconsistent sizes for einsum label {}", label);
+            }
+            *counts.entry(label).or_insert(0) += 1;
+        }
+    }
+
+    let output_labels: Vec<char> = match output {
+        Some(output) => output.chars().collect(),
+        None => {
+            let mut labels: Vec<char> = counts
+                .iter()
+                .filter(|(_, &count)| count == 1)
+                .map(|(&label, _)| label)
+                .collect();
+            labels.sort();
+            labels
+        }
+    };
+    for label in output_labels.iter() {
+        if !sizes.contains_key(label) {
+            panic!(
+                "einsum output label {} doesn't appear in any operand",
+                label
+            );
+        }
+    }
+
+    Expr::new(Einsum {
+        exprs,
+        input_labels,
+        output_labels,
+        sizes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let a = v(
+            "a",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[0.0, 1.0], [2.0, 3.0]]))),
+        );
+        let b = v(
+            "b",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]))),
+        );
+        let c = einsum("ij,jk->ik", vec![a.clone(), b.clone()]);
+        assert_eq!(c.eval(), matmul(a.clone(), b.clone()).eval());
+        assert_eq!(
+            c.gradient("a").eval(),
+            matmul(a.clone(), b.clone()).gradient("a").eval()
+        );
+        assert_eq!(
+            c.gradient("b").eval(),
+            matmul(a.clone(), b.clone()).gradient("b").eval()
+        );
+        assert_eq!(einsum("ij,jk", vec![a.clone(), b.clone()]).eval(), c.eval());
+
+        // trace
+        let trace = einsum("ii->", vec![a.clone()]);
+        assert_eq!(trace.eval(), ndarray::arr0(3.0).into_dyn());
+        assert_eq!(
+            trace.gradient("a").eval(),
+            ndarray::arr2(&[[1.0, 0.0], [0.0, 1.0]]).into_dyn()
+        );
+
+        // sum
+        assert_eq!(
+            einsum("ij->", vec![a.clone()]).gradient("a").eval(),
+            ndarray::arr2(&[[1.0, 1.0], [1.0, 1.0]]).into_dyn()
+        );
+
+        // transpose
+        assert_eq!(
+            einsum("ij->ji", vec![a.clone()]).eval(),
+            ndarray::arr2(&[[0.0, 2.0], [1.0, 3.0]]).into_dyn()
+        );
+
+        // outer product
+        let x = expr(ndarray::arr1(&[1.0, 2.0]));
+        let y = expr(ndarray::arr1(&[3.0, 4.0, 5.0]));
+        assert_eq!(
+            einsum("i,j->ij", vec![x, y]).eval(),
+            ndarray::arr2(&[[3.0, 4.0, 5.0], [6.0, 8.0, 10.0]]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_batched() {
+        let a = v(
+            "a",
+            Rc::new(VariableValue::new(ndarray::arr3(&[
+                [[0.0, 1.0], [2.0, 3.0]],
+                [[1.0, 0.0], [0.0, 1.0]],
+            ]))),
+        );
+        let b = v(
+            "b",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]))),
+        );
+        let c = einsum("bij,jk->bik", vec![a.clone(), b.clone()]);
+        let d = matmul(a.clone(), b.clone());
+        assert_eq!(c.eval(), d.eval());
+        assert_eq!(c.gradient("a").eval(), d.gradient("a").eval());
+        assert_eq!(c.gradient("b").eval(), d.gradient("b").eval());
+
+        let b_value = Rc::new(VariableValue::new(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]])));
+        let b = v("b", b_value.clone());
+        let mut tangents = HashMap::new();
+        tangents.insert(b_value.id(), expr(ndarray::arr2(&[[1.0, 0.0], [0.0, 0.0]])));
+        assert_eq!(
+            einsum("bij,jk->bik", vec![a.clone(), b.clone()])
+                .jvp(&tangents)
+                .eval(),
+            matmul(a, b).jvp(&tangents).eval()
+        );
+    }
+
+    #[test]
+    fn test_contractions() {
+        let a = expr(ndarray::Array::from_shape_fn((64, 64), |(i, j)| {
+            (i + 2 * j) as f32 / 64.0
+        }));
+        let b = expr(ndarray::Array::from_shape_fn((64, 64), |(i, j)| {
+            (i * j % 5) as f32
+        }));
+        let c = expr(ndarray::Array::from_shape_fn((64, 64), |(i, j)| {
+            i as f32 - j as f32
+        }));
+
+        // operands are contracted pairwise, which is only feasible because the 64^4 combinations
+        // of label values are never visited
+        assert_eq!(
+            einsum("ij,jk,kl->il", vec![a.clone(), b.clone(), c.clone()]).eval(),
+            matmul(matmul(a.clone(), b.clone()), c.clone()).eval()
+        );
+
+        // attention scores, batched over the shared labels that appear in the output
+        let q = expr(ndarray::Array::from_shape_fn((2, 3, 4), |(b, i, d)| {
+            (b + i * d) as f32
+        }));
+        let k = expr(ndarray::Array::from_shape_fn((2, 5, 4), |(b, j, d)| {
+            (b * j + d) as f32
+        }));
+        assert_eq!(
+            einsum("bid,bjd->bij", vec![q.clone(), k.clone()]).eval(),
+            matmul(q.clone(), transpose_axes(k.clone(), vec![0, 2, 1])).eval()
+        );
+
+        // labels that only one operand has are summed before the contraction
+        let x = expr(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]));
+        let y = expr(ndarray::arr1(&[1.0, 10.0]));
+        assert_eq!(
+            einsum("ij,k->k", vec![x.clone(), y.clone()]).eval(),
+            ndarray::arr1(&[10.0, 100.0]).into_dyn()
+        );
+
+        // diagonals can be taken from operands and written to the output
+        assert_eq!(
+            einsum("ii,i->i", vec![x.clone(), y.clone()]).eval(),
+            ndarray::arr1(&[1.0, 40.0]).into_dyn()
+        );
+        assert_eq!(
+            einsum("ij,j->ii", vec![x, y]).eval(),
+            ndarray::arr2(&[[21.0, 0.0], [0.0, 43.0]]).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes the element-wise Gauss error function of the expression.
+pub struct Erf {
+    pub expr: Expr,
+}
+
+// This is the approximation from Abramowitz and Stegun 7.1.26. Its maximum error is 1.5e-7.
+fn erf(x: f32) -> f32 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let y = 1.0
+        - t * (0.254_829_6
+            + t * (-0.284_496_74 + t * (1.421_413_8 + t * (-1.453_152 + t * 1.061_405_4))))
+            * (-x * x).exp();
+    if x < 0.0 {
+        -y
+    } else {
+        y
+    }
+}
+
+impl Erf {
+    fn derivative(&self) -> Expr {
+        2.0 / std::f32::consts::PI.sqrt() * (-1.0 * self.expr.square()).exp()
+    }
+}
+
+impl ExprImpl for Erf {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(erf)
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().erf()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Erf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "erf({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[-1.0, 0.0, 0.5, 2.0]))),
+        );
+        let y = x.erf();
+        assert!(y.eval().all_close(
+            &ndarray::arr1(&[-0.842_700_8, 0.0, 0.520_499_9, 0.995_322_3]).into_dyn(),
+            1e-6
+        ));
+        assert!(y.gradient("x").eval().all_close(
+            &ndarray::arr1(&[
+                0.415_107_5,
+                std::f32::consts::FRAC_2_SQRT_PI,
+                0.878_782_6,
+                0.020_666_985,
+            ])
+            .into_dyn(),
+            1e-6
+        ));
+    }
+}
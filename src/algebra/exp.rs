@@ -36,6 +36,12 @@ impl ExprImpl for Exp {
         vec![Some(output.clone() * Expr::new(self.clone()))]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| t.clone() * Expr::new(self.clone()))
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.power]
     }
@@ -68,4 +74,20 @@ mod tests {
             ndarray::arr1(&[1.0, 1.0, 1.0]).into_dyn()
         );
     }
+
+    #[test]
+    fn test_jvp() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr1(&[0.0, 0.0, 0.0])));
+        let x = v("x", x_value.clone());
+        let mut tangents = HashMap::new();
+        tangents.insert(x_value.id(), expr(ndarray::arr1(&[1.0, 2.0, 3.0])));
+        assert_eq!(
+            x.exp().jvp(&tangents).eval(),
+            ndarray::arr1(&[1.0, 2.0, 3.0]).into_dyn()
+        );
+        assert_eq!(
+            x.exp().sum().jvp(&tangents).eval(),
+            ndarray::arr0(6.0).into_dyn()
+        );
+    }
 }
@@ -0,0 +1,77 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes exp(x) - 1 element-wise. This is more accurate than composing exp and sub for small x.
+pub struct Expm1 {
+    pub expr: Expr,
+}
+
+impl Expm1 {
+    fn derivative(&self) -> Expr {
+        self.expr.exp()
+    }
+}
+
+impl ExprImpl for Expm1 {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| v.exp_m1())
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().expm1()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Expm1 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expm1({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[1e-10, 0.0]))),
+        );
+        let y = x.expm1();
+        assert_eq!(y.eval(), ndarray::arr1(&[1e-10, 0.0]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[1.0, 1.0]).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+// Indices are carried around as f32s like everything else. This converts them back, making sure
+// they're valid for an axis of the given length.
+fn to_indices(indices: &ndarray::ArrayD<f32>, len: usize) -> Vec<usize> {
+    indices
+        .iter()
+        .map(|&i| {
+            if i < 0.0 || i.fract() != 0.0 || i as usize >= len {
+                panic!("invalid index {} for axis of length {}", i, len);
+            }
+            i as usize
+        })
+        .collect()
+}
+
+// Calls f with a reshaped to (product of axes before axis, length of axes from axis until
+// axis + n, product of remaining axes). This is a view of a unless a isn't contiguous, in which case
+// it has to be copied.
+fn with_3d<R, F>(a: &ndarray::ArrayD<f32>, axis: usize, n: usize, f: F) -> R
+where
+    F: FnOnce(ndarray::ArrayView3<f32>) -> R,
+{
+    let shape = a.shape();
+    let shape = (
+        shape[..axis].iter().product(),
+        shape[axis..axis + n].iter().product(),
+        shape[axis + n..].iter().product(),
+    );
+    match a.view().into_shape(shape) {
+        Ok(view) => f(view),
+        Err(_) => {
+            let copy = ndarray::Array::from_shape_vec(shape, a.iter().cloned().collect()).unwrap();
+            f(copy.view())
+        }
+    }
+}
+
+// Gather selects slices of params along the given axis. The axis is replaced by the shape of the
+// indices, so gathering from params of shape (a, b, c) along axis 1 with indices of shape (d, e)
+// gives an output of shape (a, d, e, c). Indices aren't differentiable.
+pub struct Gather {
+    pub params: Expr,
+    pub indices: Expr,
+    pub axis: usize,
+}
+
+impl ExprImpl for Gather {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let (params, indices) = (&inputs[0], &inputs[1]);
+        let indices = to_indices(indices, params.shape()[self.axis]);
+        // only the selected slices are copied, so lookups don't scale with the size of params
+        let result = with_3d(params, self.axis, 1, |params| {
+            params.select(ndarray::Axis(1), &indices)
+        });
+        result.into_shape(self.shape()).unwrap()
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let params = self.params.shape();
+        let mut shape = params.slice()[..self.axis].to_vec();
+        shape.extend_from_slice(self.indices.shape().slice());
+        shape.extend_from_slice(&params.slice()[self.axis + 1..]);
+        ndarray::IxDyn(&shape)
+    }
+
+    fn is_constant(&self) -> bool {
+        self.params.is_constant() && self.indices.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            gather(
+                self.params.propagate_constants(),
+                self.indices.propagate_constants(),
+                self.axis,
+            )
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![
+            Some(scatter_add(
+                output,
+                self.indices.clone(),
+                self.axis,
+                self.params.shape(),
+            )),
+            None,
+        ]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| gather(t.clone(), self.indices.clone(), self.axis))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.params, &self.indices]
+    }
+}
+
+impl fmt::Display for Gather {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "gather({}, {}, {})",
+            self.params, self.indices, self.axis
+        )
+    }
+}
+
+// ScatterAdd is the inverse of Gather. It creates a tensor of zeros with the given shape, then adds
+// each slice of updates into the slice of the output named by the corresponding index. Duplicate
+// indices accumulate.
+pub struct ScatterAdd {
+    pub updates: Expr,
+    pub indices: Expr,
+    pub axis: usize,
+    pub shape: ndarray::IxDyn,
+}
+
+impl ExprImpl for ScatterAdd {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let (updates, indices) = (&inputs[0], &inputs[1]);
+        let indices = to_indices(indices, self.shape[self.axis]);
+        let mut result = ndarray::Array::zeros(self.shape.clone());
+        with_3d(updates, self.axis, self.indices.shape().ndim(), |updates| {
+            let shape = self.shape.slice();
+            let mut result = result
+                .view_mut()
+                .into_shape((
+                    shape[..self.axis].iter().product(),
+                    shape[self.axis],
+                    shape[self.axis + 1..].iter().product(),
+                ))
+                .unwrap();
+            for (j, &i) in indices.iter().enumerate() {
+                let mut dest = result.index_axis_mut(ndarray::Axis(1), i);
+                dest += &updates.index_axis(ndarray::Axis(1), j);
+            }
+        });
+        result
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.shape.clone()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.updates.is_constant() && self.indices.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            scatter_add(
+                self.updates.propagate_constants(),
+                self.indices.propagate_constants(),
+                self.axis,
+                self.shape.clone(),
+            )
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(gather(output, self.indices.clone(), self.axis)), None]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| {
+            scatter_add(
+                t.clone(),
+                self.indices.clone(),
+                self.axis,
+                self.shape.clone(),
+            )
+        })
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.updates, &self.indices]
+    }
+}
+
+impl fmt::Display for ScatterAdd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scatter_add({}, {}, {}, {:?})",
+            self.updates, self.indices, self.axis, self.shape
+        )
+    }
+}
+
+pub fn gather<P: Into<Expr>, I: Into<Expr>>(params: P, indices: I, axis: usize) -> Expr {
+    Expr::new(Gather {
+        params: params.into(),
+        indices: indices.into(),
+        axis,
+    })
+}
+
+pub fn scatter_add<U: Into<Expr>, I: Into<Expr>>(
+    updates: U,
+    indices: I,
+    axis: usize,
+    shape: ndarray::IxDyn,
+) -> Expr {
+    Expr::new(ScatterAdd {
+        updates: updates.into(),
+        indices: indices.into(),
+        axis,
+        shape,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        // an embedding lookup
+        let embeddings = v(
+            "e",
+            Rc::new(VariableValue::new(ndarray::arr2(&[
+                [0.0, 1.0],
+                [2.0, 3.0],
+                [4.0, 5.0],
+            ]))),
+        );
+        let tokens = expr(ndarray::arr1(&[2.0, 0.0, 2.0]));
+        let y = gather(embeddings.clone(), tokens.clone(), 0);
+        assert_eq!(
+            y.eval(),
+            ndarray::arr2(&[[4.0, 5.0], [0.0, 1.0], [4.0, 5.0]]).into_dyn()
+        );
+        assert_eq!(
+            y.gradient("e").eval(),
+            ndarray::arr2(&[[1.0, 1.0], [0.0, 0.0], [2.0, 2.0]]).into_dyn()
+        );
+
+        let y = gather(embeddings.clone(), expr(ndarray::arr2(&[[1.0], [0.0]])), 1);
+        assert_eq!(y.shape(), ndarray::IxDyn(&[3, 2, 1]));
+        assert_eq!(
+            y.eval(),
+            ndarray::arr3(&[[[1.0], [0.0]], [[3.0], [2.0]], [[5.0], [4.0]]]).into_dyn()
+        );
+
+        let updates = v(
+            "u",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]))),
+        );
+        let y = scatter_add(
+            updates.clone(),
+            expr(ndarray::arr1(&[1.0, 1.0])),
+            0,
+            ndarray::IxDyn(&[3, 2]),
+        );
+        assert_eq!(
+            y.eval(),
+            ndarray::arr2(&[[0.0, 0.0], [4.0, 6.0], [0.0, 0.0]]).into_dyn()
+        );
+        assert_eq!(
+            (y * expr(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]])))
+                .gradient("u")
+                .eval(),
+            ndarray::arr2(&[[3.0, 4.0], [3.0, 4.0]]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_non_contiguous() {
+        // params in column-major order can't be viewed as 3d, so they're copied instead
+        let params = ndarray::arr2(&[[0.0, 2.0, 4.0], [1.0, 3.0, 5.0]])
+            .reversed_axes()
+            .into_dyn();
+        assert!(!params.is_standard_layout());
+        let y = Gather {
+            params: expr(params.clone()),
+            indices: expr(ndarray::arr1(&[2.0, 0.0])),
+            axis: 0,
+        };
+        assert_eq!(
+            y.eval_inputs(&vec![params, ndarray::arr1(&[2.0, 0.0]).into_dyn()]),
+            ndarray::arr2(&[[4.0, 5.0], [0.0, 1.0]]).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use ndarray::Dimension;
+
+use super::{stack, Expr};
+
+fn basis(shape: ndarray::IxDyn, i: usize) -> ndarray::ArrayD<f32> {
+    let mut a = ndarray::Array::zeros(shape);
+    a.as_slice_mut().unwrap()[i] = 1.0;
+    a
+}
+
+// Returns the Jacobian of the expression with respect to the given variable. The result's shape is
+// the expression's shape followed by the variable's shape. Each row (or column) takes its own
+// differentiation pass, using reverse-mode or forward-mode depending on which needs fewer, so this
+// is only practical for fairly small expressions.
+pub fn jacobian(expr: Expr, variable: Expr) -> Expr {
+    let (expr_shape, variable_shape) = (expr.shape(), variable.shape());
+    let (rows, cols) = (expr_shape.size(), variable_shape.size());
+    let mut shape = expr_shape.slice().to_vec();
+    shape.extend_from_slice(variable_shape.slice());
+    if rows == 0 || cols == 0 {
+        return super::expr(ndarray::Array::zeros(ndarray::IxDyn(&shape)));
+    }
+    let result = if rows <= cols {
+        let rows = (0..rows)
+            .map(|i| {
+                (expr.clone() * super::expr(basis(expr_shape.clone(), i)))
+                    .sum()
+                    .gradients_wrt(std::slice::from_ref(&variable))
+                    .remove(0)
+                    .reshape(ndarray::Ix1(cols))
+            })
+            .collect();
+        stack(rows, 0)
+    } else {
+        let id = match variable.as_variable() {
+            Some(v) => v.value.id(),
+            None => panic!("jacobians can only be taken with respect to variables"),
+        };
+        let columns = (0..cols)
+            .map(|j| {
+                let mut tangents = HashMap::new();
+                tangents.insert(id, super::expr(basis(variable_shape.clone(), j)));
+                expr.jvp(&tangents).reshape(ndarray::Ix1(rows))
+            })
+            .collect();
+        stack(columns, 1)
+    };
+    result.reshape(ndarray::IxDyn(&shape))
+}
+
+// Returns the Hessian of a scalar expression with respect to the given variable. The result's shape
+// is the variable's shape repeated twice.
+pub fn hessian(expr: Expr, variable: Expr) -> Expr {
+    if expr.shape().ndim() != 0 {
+        panic!("hessians can only be taken of scalar expressions");
+    }
+    let gradient = expr
+        .gradients_wrt(std::slice::from_ref(&variable))
+        .remove(0);
+    jacobian(gradient, variable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    // Approximates the Jacobian of f with central differences.
+    fn finite_differences<F: Fn() -> Expr>(f: F, value: &VariableValue) -> ndarray::Array2<f32> {
+        let epsilon = 1e-2;
+        let x = value.get();
+        let rows = f().shape().size();
+        let mut result = ndarray::Array::zeros((rows, x.len()));
+        for j in 0..x.len() {
+            let mut plus = x.clone();
+            plus.as_slice_mut().unwrap()[j] += epsilon;
+            value.set(plus);
+            let high = f().eval().into_shape(rows).unwrap();
+            let mut minus = x.clone();
+            minus.as_slice_mut().unwrap()[j] -= epsilon;
+            value.set(minus);
+            let low = f().eval().into_shape(rows).unwrap();
+            result
+                .column_mut(j)
+                .assign(&((high - low) / (2.0 * epsilon)));
+        }
+        value.set(x);
+        result
+    }
+
+    fn assert_close(a: ndarray::ArrayD<f32>, b: ndarray::Array2<f32>) {
+        let a = a.into_shape(b.dim()).unwrap();
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert!((a - b).abs() < 1e-2, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_jacobian() {
+        let a = ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        let x = v("x", Rc::new(VariableValue::new(ndarray::arr1(&[1.0, 2.0]))));
+        assert_eq!(
+            jacobian(matvecmul(a.clone(), x.clone()), x.clone()).eval(),
+            a.clone().into_dyn()
+        );
+        assert_eq!(
+            jacobian(x.clone(), x.clone()).eval(),
+            ndarray::arr2(&[[1.0, 0.0], [0.0, 1.0]]).into_dyn()
+        );
+
+        let value = Rc::new(VariableValue::new(ndarray::arr1(&[0.1, 0.5, -0.3])));
+        let x = v("x", value.clone());
+        let f = || x.softmax();
+        assert_close(
+            jacobian(f(), x.clone()).eval(),
+            finite_differences(f, &value),
+        );
+
+        let value = Rc::new(VariableValue::new(ndarray::arr2(&[
+            [0.1, 0.5],
+            [-0.3, 0.2],
+        ])));
+        let x = v("x", value.clone());
+        let f = || (x.clone() * 2.0).exp().sum();
+        let j = jacobian(f(), x.clone());
+        assert_eq!(j.shape(), ndarray::IxDyn(&[2, 2]));
+        assert_close(j.eval(), finite_differences(f, &value));
+    }
+
+    #[test]
+    fn test_hessian() {
+        let value = Rc::new(VariableValue::new(ndarray::arr1(&[1.0, -2.0, 3.0])));
+        let x = v("x", value.clone());
+        assert_eq!(
+            hessian((x.square() * x.clone()).sum(), x.clone()).eval(),
+            ndarray::arr2(&[[6.0, 0.0, 0.0], [0.0, -12.0, 0.0], [0.0, 0.0, 18.0]]).into_dyn()
+        );
+        assert_eq!(
+            hessian(x.max(expr(0.0)).square().sum(), x.clone()).eval(),
+            ndarray::arr2(&[[2.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 2.0]]).into_dyn()
+        );
+
+        let value = Rc::new(VariableValue::new(ndarray::arr1(&[0.1, 0.5, -0.3])));
+        let x = v("x", value.clone());
+        let truth = expr(ndarray::arr1(&[0.0, 1.0, 0.0]));
+        let f = || {
+            let loss = expr(0.0) - (truth.clone() * x.softmax().ln()).sum();
+            loss.gradients_wrt(std::slice::from_ref(&x)).remove(0)
+        };
+        assert_close(
+            hessian(
+                expr(0.0) - (truth.clone() * x.softmax().ln()).sum(),
+                x.clone(),
+            )
+            .eval(),
+            finite_differences(f, &value),
+        );
+    }
+}
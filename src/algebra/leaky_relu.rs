@@ -0,0 +1,89 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// LeakyRelu outputs x where x is positive and alpha * x elsewhere in a single pass.
+pub struct LeakyRelu {
+    pub expr: Expr,
+    pub alpha: f32,
+}
+
+impl ExprImpl for LeakyRelu {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| if v > 0.0 { v } else { self.alpha * v })
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            leaky_relu(self.expr.propagate_constants(), self.alpha)
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(super::relu_gradient(
+            output,
+            self.expr.clone(),
+            self.alpha,
+        ))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| super::relu_gradient(t.clone(), self.expr.clone(), self.alpha))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for LeakyRelu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "leaky_relu({}, {})", self.expr, self.alpha)
+    }
+}
+
+pub fn leaky_relu<V: Into<Expr>>(expr: V, alpha: f32) -> Expr {
+    Expr::new(LeakyRelu {
+        expr: expr.into(),
+        alpha,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[-1.0, 0.0, 2.0]))),
+        );
+        let y = leaky_relu(x, 0.1);
+        assert_eq!(y.eval(), ndarray::arr1(&[-0.1, 0.0, 2.0]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[0.1, 0.1, 1.0]).into_dyn()
+        );
+        assert_eq!(
+            y.gradient("x").gradient("x").eval(),
+            ndarray::arr0(0.0).into_dyn()
+        );
+    }
+}
@@ -35,6 +35,10 @@ impl ExprImpl for Ln {
         vec![Some(output.clone() / self.expr.clone())]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() / self.expr.clone())
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes ln(1 + x) element-wise. This is more accurate than composing ln and add for small x.
+pub struct Log1p {
+    pub expr: Expr,
+}
+
+impl Log1p {
+    fn derivative(&self) -> Expr {
+        1.0 / (1.0 + self.expr.clone())
+    }
+}
+
+impl ExprImpl for Log1p {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| v.ln_1p())
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().log1p()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Log1p {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "log1p({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[1e-10, 1.0]))),
+        );
+        let y = x.log1p();
+        assert_eq!(
+            y.eval(),
+            ndarray::arr1(&[1e-10, std::f32::consts::LN_2]).into_dyn()
+        );
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[1.0, 0.5]).into_dyn()
+        );
+    }
+}
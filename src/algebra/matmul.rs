@@ -4,26 +4,131 @@ use super::{Expr, ExprImpl};
 
 use ndarray::Dimension;
 
-// MatMul performs matrix-matrix multiplication.
+// Returns the broadcasted batch shape for a batched matrix multiplication. The batch dimensions are
+// everything but the last two, aligned to the right. Each pair must either be equal or contain a 1.
+fn batch_shape(a: &ndarray::IxDyn, b: &ndarray::IxDyn) -> Vec<usize> {
+    let (a, b) = (&a.slice()[..a.ndim() - 2], &b.slice()[..b.ndim() - 2]);
+    let ndim = std::cmp::max(a.len(), b.len());
+    (0..ndim)
+        .map(|i| {
+            let a = if i + a.len() >= ndim {
+                a[i + a.len() - ndim]
+            } else {
+                1
+            };
+            let b = if i + b.len() >= ndim {
+                b[i + b.len() - ndim]
+            } else {
+                1
+            };
+            if a != b && a != 1 && b != 1 {
+                panic!(
+                    "incompatible batch dimensions for matmul: {:?} and {:?}",
+                    a, b
+                );
+            }
+            std::cmp::max(a, b)
+        })
+        .collect()
+}
+
+// Broadcasts a to the given batch shape and flattens it to (batch size, rows, cols).
+fn flatten_batch(a: &ndarray::ArrayD<f32>, batch: &[usize]) -> ndarray::Array3<f32> {
+    let (rows, cols) = (a.shape()[a.ndim() - 2], a.shape()[a.ndim() - 1]);
+    let mut shape = batch.to_vec();
+    shape.extend_from_slice(&[rows, cols]);
+    let broadcast = a.broadcast(shape).unwrap();
+    ndarray::Array::from_shape_vec(
+        (batch.iter().product(), rows, cols),
+        broadcast.iter().cloned().collect(),
+    )
+    .unwrap()
+}
+
+// Multiplies each pair of matrices in two batches of the same size.
+pub(crate) fn mat_mul_batches(
+    a: ndarray::ArrayView3<f32>,
+    b: ndarray::ArrayView3<f32>,
+) -> ndarray::Array3<f32> {
+    let (batch_size, rows, cols) = (a.shape()[0], a.shape()[1], b.shape()[2]);
+    let mut result = ndarray::Array::zeros((batch_size, rows, cols));
+    for i in 0..batch_size {
+        ndarray::linalg::general_mat_mul(
+            1.0,
+            &a.index_axis(ndarray::Axis(0), i),
+            &b.index_axis(ndarray::Axis(0), i),
+            0.0,
+            &mut result.index_axis_mut(ndarray::Axis(0), i),
+        );
+    }
+    result
+}
+
+// Sums a batched gradient over the batch dimensions that were broadcasted for the given shape.
+fn reduce_batch(gradient: Expr, shape: ndarray::IxDyn) -> Expr {
+    let gradient_shape = gradient.shape();
+    if gradient_shape == shape {
+        return gradient;
+    }
+    let missing = gradient_shape.ndim() - shape.ndim();
+    let mut reduction_axes: Vec<_> = (0..missing).collect();
+    for i in 0..shape.ndim() - 2 {
+        if shape[i] == 1 && gradient_shape[missing + i] != 1 {
+            reduction_axes.push(missing + i);
+        }
+    }
+    super::reduce_sum(gradient, reduction_axes).reshape(shape)
+}
+
+// Swaps the last two axes.
+fn transpose_matrices(a: &Expr) -> Expr {
+    let ndim = a.shape().ndim();
+    let mut axes: Vec<_> = (0..ndim).collect();
+    axes.swap(ndim - 2, ndim - 1);
+    super::transpose_axes(a.clone(), axes)
+}
+
+// MatMul performs matrix-matrix multiplication. If either operand has more than two dimensions,
+// the leading dimensions are treated as batch dimensions and broadcasted against each other, and a
+// separate multiplication is performed for each matrix in the batch.
 pub struct MatMul {
     pub a: Expr,
     pub b: Expr,
 }
 
+impl MatMul {
+    fn is_batched(&self) -> bool {
+        self.a.shape().ndim() > 2 || self.b.shape().ndim() > 2
+    }
+}
+
 impl ExprImpl for MatMul {
     fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
         let (a, b) = (&inputs[0], &inputs[1]);
-        let a = a.clone().into_dimensionality::<ndarray::Ix2>().unwrap();
-        let b = b.clone().into_dimensionality::<ndarray::Ix2>().unwrap();
-        let mut result = ndarray::Array::zeros((a.rows(), b.cols()));
-        ndarray::linalg::general_mat_mul(1.0, &a, &b, 0.0, &mut result);
-        result.into_dyn()
+        if !self.is_batched() {
+            let a = a.clone().into_dimensionality::<ndarray::Ix2>().unwrap();
+            let b = b.clone().into_dimensionality::<ndarray::Ix2>().unwrap();
+            let mut result = ndarray::Array::zeros((a.rows(), b.cols()));
+            ndarray::linalg::general_mat_mul(1.0, &a, &b, 0.0, &mut result);
+            return result.into_dyn();
+        }
+        let batch = batch_shape(&a.raw_dim(), &b.raw_dim());
+        let (a, b) = (flatten_batch(a, &batch), flatten_batch(b, &batch));
+        mat_mul_batches(a.view(), b.view())
+            .into_shape(self.shape())
+            .unwrap()
     }
 
     fn shape(&self) -> ndarray::IxDyn {
-        let rows = self.a.shape().as_array_view()[0];
-        let cols = self.b.shape().as_array_view()[1];
-        ndarray::Ix2(rows, cols).into_dyn()
+        let (a, b) = (self.a.shape(), self.b.shape());
+        if !self.is_batched() {
+            let rows = a.as_array_view()[0];
+            let cols = b.as_array_view()[1];
+            return ndarray::Ix2(rows, cols).into_dyn();
+        }
+        let mut shape = batch_shape(&a, &b);
+        shape.extend_from_slice(&[a[a.ndim() - 2], b[b.ndim() - 1]]);
+        ndarray::IxDyn(&shape)
     }
 
     fn is_constant(&self) -> bool {
@@ -55,12 +160,37 @@ impl ExprImpl for MatMul {
         output: Expr,
         _gradients: &mut super::Gradients,
     ) -> Vec<Option<Expr>> {
+        if !self.is_batched() {
+            return vec![
+                Some(matmul(output.clone(), self.b.transpose())),
+                Some(matmul(self.a.transpose(), output.clone())),
+            ];
+        }
         vec![
-            Some(matmul(output.clone(), self.b.transpose())),
-            Some(matmul(self.a.transpose(), output.clone())),
+            Some(reduce_batch(
+                matmul(output.clone(), transpose_matrices(&self.b)),
+                self.a.shape(),
+            )),
+            Some(reduce_batch(
+                matmul(transpose_matrices(&self.a), output),
+                self.b.shape(),
+            )),
         ]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        let a = tangents[0]
+            .as_ref()
+            .map(|a| matmul(a.clone(), self.b.clone()));
+        let b = tangents[1]
+            .as_ref()
+            .map(|b| matmul(self.a.clone(), b.clone()));
+        match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        }
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.a, &self.b]
     }
@@ -78,3 +208,61 @@ pub fn matmul<A: Into<Expr>, B: Into<Expr>>(a: A, b: B) -> Expr {
         b: b.into(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let a = v(
+            "a",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[0.0, 1.0], [2.0, 3.0]]))),
+        );
+        let b = v(
+            "b",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]))),
+        );
+        assert_eq!(
+            matmul(a.clone(), b.clone()).eval(),
+            ndarray::arr2(&[[3.0, 4.0], [11.0, 16.0]]).into_dyn()
+        );
+        assert_eq!(
+            matmul(a.clone(), b.clone()).gradient("a").eval(),
+            ndarray::arr2(&[[3.0, 7.0], [3.0, 7.0]]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_batched() {
+        let a = v(
+            "a",
+            Rc::new(VariableValue::new(ndarray::arr3(&[
+                [[0.0, 1.0], [2.0, 3.0]],
+                [[1.0, 0.0], [0.0, 1.0]],
+            ]))),
+        );
+        let b = v(
+            "b",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]))),
+        );
+        let c = matmul(a.clone(), b.clone());
+        assert_eq!(c.shape(), ndarray::IxDyn(&[2, 2, 2]));
+        assert_eq!(
+            c.eval(),
+            ndarray::arr3(&[[[3.0, 4.0], [11.0, 16.0]], [[1.0, 2.0], [3.0, 4.0]]]).into_dyn()
+        );
+        assert_eq!(
+            c.gradient("a").eval(),
+            ndarray::arr3(&[[[3.0, 7.0], [3.0, 7.0]], [[3.0, 7.0], [3.0, 7.0]]]).into_dyn()
+        );
+        assert_eq!(
+            c.gradient("b").eval(),
+            ndarray::arr2(&[[3.0, 3.0], [5.0, 5.0]]).into_dyn()
+        );
+
+        let a = expr(ndarray::Array::ones((3, 1, 2, 4)));
+        let b = expr(ndarray::Array::ones((5, 4, 6)));
+        assert_eq!(matmul(a, b).shape(), ndarray::IxDyn(&[3, 5, 2, 6]));
+    }
+}
@@ -62,6 +62,19 @@ impl ExprImpl for MatVecMul {
         ]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        let a = tangents[0]
+            .as_ref()
+            .map(|a| matvecmul(a.clone(), self.b.clone()));
+        let b = tangents[1]
+            .as_ref()
+            .map(|b| matvecmul(self.a.clone(), b.clone()));
+        match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        }
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.a, &self.b]
     }
@@ -128,4 +141,25 @@ mod tests {
             ndarray::arr1(&[8.0, 14.0]).into_dyn()
         );
     }
+
+    #[test]
+    fn test_jvp() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr2(&[[0.0, 1.0], [2.0, 3.0]])));
+        let y_value = Rc::new(VariableValue::new(ndarray::arr1(&[3.0, 5.0])));
+        let x = v("x", x_value.clone());
+        let y = v("y", y_value.clone());
+
+        let mut tangents = HashMap::new();
+        tangents.insert(y_value.id(), expr(ndarray::arr1(&[1.0, 0.0])));
+        assert_eq!(
+            matvecmul(x.clone(), y.clone()).jvp(&tangents).eval(),
+            ndarray::arr1(&[0.0, 2.0]).into_dyn()
+        );
+
+        tangents.insert(x_value.id(), expr(ndarray::arr2(&[[1.0, 0.0], [0.0, 0.0]])));
+        assert_eq!(
+            matvecmul(x, y).jvp(&tangents).eval(),
+            ndarray::arr1(&[3.0, 2.0]).into_dyn()
+        );
+    }
 }
@@ -5,34 +5,86 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use ndarray::Dimension;
 
+pub mod abs;
+pub use abs::*;
 pub mod add;
 pub use add::*;
 pub mod broadcast_to;
 pub use broadcast_to::*;
+pub mod clip;
+pub use clip::*;
 pub mod cmp;
 pub use cmp::*;
+pub mod concat;
+pub use concat::*;
 pub mod conv2d;
 pub use conv2d::*;
+pub mod cos;
+pub use cos::*;
+pub mod custom_gradient;
+pub use custom_gradient::*;
 pub mod div;
 pub use div::*;
+pub mod einsum;
+pub use einsum::*;
+pub mod erf;
+pub use erf::*;
 pub mod exp;
 pub use exp::*;
+pub mod expm1;
+pub use expm1::*;
+pub mod gather;
+pub use gather::*;
+pub mod jacobian;
+pub use jacobian::*;
 pub mod ternary;
 pub use ternary::*;
+pub mod leaky_relu;
+pub use leaky_relu::*;
 pub mod ln;
 pub use ln::*;
+pub mod log1p;
+pub use log1p::*;
 pub mod matmul;
 pub use matmul::*;
 pub mod matvecmul;
 pub use matvecmul::*;
 pub mod mul;
 pub use mul::*;
+pub mod one_hot;
+pub use one_hot::*;
+pub mod pad;
+pub use pad::*;
+pub mod permute;
+pub use permute::*;
+pub mod pow;
+pub use pow::*;
+pub mod reciprocal;
+pub use reciprocal::*;
 pub mod reduce_sum;
 pub use reduce_sum::*;
+pub mod relu;
+pub use relu::*;
 pub mod reshape;
 pub use reshape::*;
+pub mod round;
+pub use round::*;
+pub mod rsqrt;
+pub use rsqrt::*;
+pub mod sigmoid;
+pub use sigmoid::*;
+pub mod sign;
+pub use sign::*;
+pub mod sin;
+pub use sin::*;
+pub mod slice;
+pub use slice::*;
 pub mod softmax;
 pub use softmax::*;
+pub mod softplus;
+pub use softplus::*;
+pub mod stop_gradient;
+pub use stop_gradient::*;
 pub mod sub;
 pub use sub::*;
 pub mod square;
@@ -41,6 +93,10 @@ pub mod sqrt;
 pub use sqrt::*;
 pub mod sum;
 pub use sum::*;
+pub mod tanh;
+pub use tanh::*;
+pub mod tile;
+pub use tile::*;
 pub mod transpose;
 pub use transpose::*;
 pub mod variable;
@@ -62,8 +118,12 @@ pub struct Expr {
     id: usize,
 }
 
+// Gradients maps variables to the gradient expressions accumulated for them. Variables are keyed by
+// their VariableValue id rather than by name so that distinct variables which happen to share a
+// name are never conflated.
 pub struct Gradients {
-    pub expressions: HashMap<String, Expr>,
+    pub expressions: HashMap<usize, Expr>,
+    pub names: HashMap<usize, String>,
 }
 
 pub trait ExprImpl: fmt::Display {
@@ -74,6 +134,17 @@ pub trait ExprImpl: fmt::Display {
     fn inputs(&self) -> Vec<&Expr>;
     fn accumulate_gradients(&self, output: Expr, gradients: &mut Gradients) -> Vec<Option<Expr>>;
 
+    // Computes the tangent of the output given the tangents of the inputs. This is the
+    // forward-mode counterpart to accumulate_gradients. A tangent of None means the input doesn't
+    // depend on any of the variables being differentiated, and the result should be None if the
+    // output doesn't either.
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr>;
+
+    // Returns the underlying variable if this expression is one.
+    fn as_variable(&self) -> Option<&Variable> {
+        None
+    }
+
     fn eval(&self) -> ndarray::ArrayD<f32> {
         let mut inputs = Vec::new();
         for input in self.inputs() {
@@ -102,9 +173,10 @@ impl Expr {
     // blow up for complex graphs (try calling it on darknet53 for example). A big potential
     // optimization would be to visit expressions in dependency-order, guaranteeing that
     // accumulate_gradients is not called more than once per expression.
-    pub fn gradients(&self) -> HashMap<String, Expr> {
+    pub fn gradients(&self) -> Gradients {
         let mut gradients = Gradients {
             expressions: HashMap::new(),
+            names: HashMap::new(),
         };
         let mut to_visit = Vec::new();
         to_visit.push((self.clone(), expr(ndarray::Array::ones(self.shape()))));
@@ -122,11 +194,88 @@ impl Expr {
                 }
             }
         }
-        gradients.expressions
+        gradients
     }
 
+    // Returns the gradients with respect to each of the given variables, in the same order. Variables
+    // that this expression doesn't depend on get a gradient of zeros.
+    pub fn gradients_wrt(&self, variables: &[Expr]) -> Vec<Expr> {
+        let gradients = self.gradients();
+        variables
+            .iter()
+            .map(|v| {
+                let id = match v.as_variable() {
+                    Some(v) => v.value.id(),
+                    None => panic!("gradients can only be taken with respect to variables"),
+                };
+                match gradients.expressions.get(&id) {
+                    Some(grad) => grad.clone(),
+                    None => expr(ndarray::Array::zeros(v.shape())),
+                }
+            })
+            .collect()
+    }
+
+    // Returns the gradient with respect to the variable with the given name. This is mostly a
+    // convenience for tests. It panics if more than one distinct variable has the name.
     pub fn gradient(&self, v: &str) -> Expr {
-        self.gradients().get(v).unwrap_or(&expr(0.0)).simplified()
+        let gradients = self.gradients();
+        let ids: Vec<_> = gradients
+            .names
+            .iter()
+            .filter(|(_, name)| name.as_str() == v)
+            .map(|(&id, _)| id)
+            .collect();
+        if ids.len() > 1 {
+            panic!("ambiguous variable name for gradient: {}", v);
+        }
+        match ids.first() {
+            Some(id) => gradients.expressions[id].simplified(),
+            None => expr(0.0),
+        }
+    }
+
+    // This performs forward-mode automatic differentiation, returning the Jacobian-vector product
+    // of this expression with the given tangents. The tangents are keyed by VariableValue id and
+    // must have the same shape as their variables. Variables without a tangent are treated as
+    // constants.
+    //
+    // Whereas reverse-mode gets the gradients with respect to every variable in one pass,
+    // forward-mode gets the derivatives of every output element in one pass. It's the more
+    // efficient choice when there are few inputs of interest.
+    pub fn jvp(&self, tangents: &HashMap<usize, Expr>) -> Expr {
+        let mut memo = HashMap::new();
+        match self.jvp_impl(tangents, &mut memo) {
+            Some(t) => t,
+            None => expr(ndarray::Array::zeros(self.shape())),
+        }
+    }
+
+    fn jvp_impl(
+        &self,
+        tangents: &HashMap<usize, Expr>,
+        memo: &mut HashMap<usize, Option<Expr>>,
+    ) -> Option<Expr> {
+        if let Some(t) = memo.get(&self.id) {
+            return t.clone();
+        }
+        let t = match self.as_variable() {
+            Some(v) => tangents.get(&v.value.id()).cloned(),
+            None => {
+                let input_tangents: Vec<_> = self
+                    .inputs()
+                    .iter()
+                    .map(|input| input.jvp_impl(tangents, memo))
+                    .collect();
+                if input_tangents.iter().all(|t| t.is_none()) {
+                    None
+                } else {
+                    self.jvp_inputs(&input_tangents)
+                }
+            }
+        };
+        memo.insert(self.id, t.clone());
+        t
     }
 
     pub fn max(&self, b: Expr) -> Expr {
@@ -155,6 +304,90 @@ impl Expr {
         Expr::new(ln::Ln { expr: self.clone() })
     }
 
+    pub fn log1p(&self) -> Expr {
+        Expr::new(log1p::Log1p { expr: self.clone() })
+    }
+
+    pub fn expm1(&self) -> Expr {
+        Expr::new(expm1::Expm1 { expr: self.clone() })
+    }
+
+    pub fn pow<T: Into<Expr>>(&self, exponent: T) -> Expr {
+        Expr::new(pow::Pow {
+            base: self.clone(),
+            exponent: exponent.into(),
+        })
+    }
+
+    pub fn rsqrt(&self) -> Expr {
+        Expr::new(rsqrt::Rsqrt { expr: self.clone() })
+    }
+
+    pub fn reciprocal(&self) -> Expr {
+        Expr::new(reciprocal::Reciprocal { expr: self.clone() })
+    }
+
+    pub fn tanh(&self) -> Expr {
+        Expr::new(tanh::Tanh { expr: self.clone() })
+    }
+
+    pub fn sigmoid(&self) -> Expr {
+        Expr::new(sigmoid::Sigmoid { expr: self.clone() })
+    }
+
+    pub fn softplus(&self) -> Expr {
+        Expr::new(softplus::Softplus { expr: self.clone() })
+    }
+
+    pub fn erf(&self) -> Expr {
+        Expr::new(erf::Erf { expr: self.clone() })
+    }
+
+    pub fn sin(&self) -> Expr {
+        Expr::new(sin::Sin { expr: self.clone() })
+    }
+
+    pub fn cos(&self) -> Expr {
+        Expr::new(cos::Cos { expr: self.clone() })
+    }
+
+    pub fn abs(&self) -> Expr {
+        Expr::new(abs::Abs { expr: self.clone() })
+    }
+
+    pub fn sign(&self) -> Expr {
+        Expr::new(sign::Sign { expr: self.clone() })
+    }
+
+    pub fn floor(&self) -> Expr {
+        Expr::new(round::Round {
+            expr: self.clone(),
+            mode: round::RoundingMode::Floor,
+        })
+    }
+
+    pub fn ceil(&self) -> Expr {
+        Expr::new(round::Round {
+            expr: self.clone(),
+            mode: round::RoundingMode::Ceil,
+        })
+    }
+
+    pub fn round(&self) -> Expr {
+        Expr::new(round::Round {
+            expr: self.clone(),
+            mode: round::RoundingMode::Nearest,
+        })
+    }
+
+    pub fn clip(&self, min: f32, max: f32) -> Expr {
+        Expr::new(clip::Clip {
+            expr: self.clone(),
+            min,
+            max,
+        })
+    }
+
     pub fn sum(&self) -> Expr {
         Expr::new(sum::Sum { expr: self.clone() })
     }
@@ -227,6 +460,26 @@ impl ExprImpl for Expr {
         self.expr.accumulate_gradients(output, gradients)
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        let mut result = self.expr.jvp_inputs(tangents)?;
+        if result.shape().ndim() == 0 && self.shape().ndim() > 0 {
+            // expand tangents of scalars when we broadcast them
+            result = broadcast_to(result, self.shape());
+        }
+        if result.shape() != self.shape() {
+            panic!(
+                "incorrect result shape for jvp_inputs. got {:?}, expected {:?}",
+                result.shape(),
+                self.shape()
+            );
+        }
+        Some(result)
+    }
+
+    fn as_variable(&self) -> Option<&Variable> {
+        self.expr.as_variable()
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         self.expr.inputs()
     }
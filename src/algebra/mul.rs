@@ -78,6 +78,17 @@ impl ExprImpl for Mul {
         ]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        match (&tangents[0], &tangents[1]) {
+            (Some(left), Some(right)) => {
+                Some(left.clone() * self.right.clone() + self.left.clone() * right.clone())
+            }
+            (Some(left), None) => Some(left.clone() * self.right.clone()),
+            (None, Some(right)) => Some(self.left.clone() * right.clone()),
+            (None, None) => None,
+        }
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.left, &self.right]
     }
@@ -154,4 +165,31 @@ mod tests {
             ndarray::arr1(&[0.0, 1.0, 5.0]).into_dyn()
         );
     }
+
+    #[test]
+    fn test_jvp() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr1(&[0.0, 1.0, 2.0])));
+        let x = v("x", x_value.clone());
+        let y = expr(ndarray::arr1(&[0.0, 1.0, 5.0]));
+        let mut tangents = HashMap::new();
+        tangents.insert(x_value.id(), expr(ndarray::arr1(&[1.0, 1.0, 1.0])));
+        assert_eq!(
+            (x.clone() * y).jvp(&tangents).eval(),
+            ndarray::arr1(&[0.0, 1.0, 5.0]).into_dyn()
+        );
+        assert_eq!(
+            (x.clone() * x).jvp(&tangents).eval(),
+            ndarray::arr1(&[0.0, 2.0, 4.0]).into_dyn()
+        );
+
+        let x_value = Rc::new(VariableValue::new(ndarray::arr0(2.0)));
+        let x = v("x", x_value.clone());
+        let y = expr(ndarray::arr1(&[0.0, 1.0, 2.0]));
+        let mut tangents = HashMap::new();
+        tangents.insert(x_value.id(), expr(1.0));
+        assert_eq!(
+            (x * y).jvp(&tangents).eval(),
+            ndarray::arr1(&[0.0, 1.0, 2.0]).into_dyn()
+        );
+    }
 }
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+// OneHot converts indices into one-hot vectors of the given depth, adding a trailing axis. Indices
+// outside of [0, depth) produce vectors of all zeros. Indices aren't differentiable.
+pub struct OneHot {
+    pub indices: Expr,
+    pub depth: usize,
+}
+
+impl ExprImpl for OneHot {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let mut result = ndarray::Array::zeros((inputs[0].len(), self.depth));
+        for (i, &index) in inputs[0].iter().enumerate() {
+            if index >= 0.0 && index.fract() == 0.0 && (index as usize) < self.depth {
+                result[(i, index as usize)] = 1.0;
+            }
+        }
+        result.into_shape(self.shape()).unwrap()
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let mut shape = self.indices.shape().slice().to_vec();
+        shape.push(self.depth);
+        ndarray::IxDyn(&shape)
+    }
+
+    fn is_constant(&self) -> bool {
+        self.indices.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            one_hot(self.indices.propagate_constants(), self.depth)
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        _output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![None]
+    }
+
+    fn jvp_inputs(&self, _tangents: &[Option<Expr>]) -> Option<Expr> {
+        None
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.indices]
+    }
+}
+
+impl fmt::Display for OneHot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "one_hot({}, {})", self.indices, self.depth)
+    }
+}
+
+pub fn one_hot<V: Into<Expr>>(indices: V, depth: usize) -> Expr {
+    Expr::new(OneHot {
+        indices: indices.into(),
+        depth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        assert_eq!(
+            one_hot(expr(2.0), 3).eval(),
+            ndarray::arr1(&[0.0, 0.0, 1.0]).into_dyn()
+        );
+        assert_eq!(
+            one_hot(expr(ndarray::arr1(&[0.0, 3.0, 1.0])), 3).eval(),
+            ndarray::arr2(&[[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0]]).into_dyn()
+        );
+
+        let label = v("label", Rc::new(VariableValue::new(ndarray::arr0(1.0))));
+        assert_eq!(
+            one_hot(label, 3).gradient("label").eval(),
+            ndarray::arr0(0.0).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+#[derive(Clone, Copy, Debug)]
+pub enum PadMode {
+    // Fills the padding with the given value.
+    Constant(f32),
+    // Mirrors the input, excluding the edge. For example, [1, 2, 3] padded by 2 on each side becomes
+    // [3, 2, 1, 2, 3, 2, 1].
+    Reflect,
+    // Repeats the edge. For example, [1, 2, 3] padded by 2 on each side becomes
+    // [1, 1, 1, 2, 3, 3, 3].
+    Replicate,
+}
+
+impl fmt::Display for PadMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PadMode::Constant(v) => write!(f, "CONSTANT({})", v),
+            PadMode::Reflect => write!(f, "REFLECT"),
+            PadMode::Replicate => write!(f, "REPLICATE"),
+        }
+    }
+}
+
+impl PadMode {
+    // Returns the index along an axis of the given length that the padded index takes its value
+    // from, or None if it takes the constant value.
+    fn source_index(&self, len: usize, before: usize, i: usize) -> Option<usize> {
+        if i >= before && i < before + len {
+            return Some(i - before);
+        }
+        match self {
+            PadMode::Constant(_) => None,
+            PadMode::Reflect => Some(if i < before {
+                before - i
+            } else {
+                2 * (len - 1) + before - i
+            }),
+            PadMode::Replicate => Some(if i < before { 0 } else { len - 1 }),
+        }
+    }
+
+    fn without_constant(&self) -> PadMode {
+        match self {
+            PadMode::Constant(_) => PadMode::Constant(0.0),
+            mode => *mode,
+        }
+    }
+}
+
+fn pad_axis(
+    a: &ndarray::ArrayD<f32>,
+    axis: usize,
+    (before, after): (usize, usize),
+    mode: PadMode,
+) -> ndarray::ArrayD<f32> {
+    let len = a.shape()[axis];
+    let mut shape = a.raw_dim();
+    shape[axis] = before + len + after;
+    let fill = match mode {
+        PadMode::Constant(v) => v,
+        _ => 0.0,
+    };
+    let mut result = ndarray::Array::from_elem(shape, fill);
+    for i in 0..before + len + after {
+        if let Some(source) = mode.source_index(len, before, i) {
+            result
+                .index_axis_mut(ndarray::Axis(axis), i)
+                .assign(&a.index_axis(ndarray::Axis(axis), source));
+        }
+    }
+    result
+}
+
+// The transpose of pad_axis: each padded element's value is added back into its source.
+fn unpad_axis(
+    a: &ndarray::ArrayD<f32>,
+    axis: usize,
+    (before, after): (usize, usize),
+    mode: PadMode,
+) -> ndarray::ArrayD<f32> {
+    let len = a.shape()[axis] - before - after;
+    let mut shape = a.raw_dim();
+    shape[axis] = len;
+    let mut result = ndarray::Array::zeros(shape);
+    for i in 0..before + len + after {
+        if let Some(source) = mode.source_index(len, before, i) {
+            let mut dest = result.index_axis_mut(ndarray::Axis(axis), source);
+            dest += &a.index_axis(ndarray::Axis(axis), i);
+        }
+    }
+    result
+}
+
+// Pad adds padding before and after each axis.
+pub struct Pad {
+    pub expr: Expr,
+    pub padding: Vec<(usize, usize)>,
+    pub mode: PadMode,
+}
+
+impl ExprImpl for Pad {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let mut result = inputs[0].clone();
+        for (axis, &padding) in self.padding.iter().enumerate() {
+            result = pad_axis(&result, axis, padding, self.mode);
+        }
+        result
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let mut shape = self.expr.shape();
+        if shape.ndim() != self.padding.len() {
+            panic!(
+                "incorrect number of paddings. got {}, expected {}",
+                self.padding.len(),
+                shape.ndim()
+            );
+        }
+        for (axis, &(before, after)) in self.padding.iter().enumerate() {
+            shape[axis] += before + after;
+        }
+        shape
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            pad(
+                self.expr.propagate_constants(),
+                self.padding.clone(),
+                self.mode,
+            )
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(Expr::new(PadGradient {
+            expr: output,
+            padding: self.padding.clone(),
+            mode: self.mode.without_constant(),
+        }))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| {
+            pad(
+                t.clone(),
+                self.padding.clone(),
+                self.mode.without_constant(),
+            )
+        })
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Pad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pad({}, {:?}, {})", self.expr, self.padding, self.mode)
+    }
+}
+
+// PadGradient is the transpose of Pad. It removes the padding, adding padded values back into the
+// elements they were copied from.
+pub struct PadGradient {
+    pub expr: Expr,
+    pub padding: Vec<(usize, usize)>,
+    pub mode: PadMode,
+}
+
+impl ExprImpl for PadGradient {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let mut result = inputs[0].clone();
+        for (axis, &padding) in self.padding.iter().enumerate() {
+            result = unpad_axis(&result, axis, padding, self.mode);
+        }
+        result
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let mut shape = self.expr.shape();
+        for (axis, &(before, after)) in self.padding.iter().enumerate() {
+            shape[axis] -= before + after;
+        }
+        shape
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            Expr::new(PadGradient {
+                expr: self.expr.propagate_constants(),
+                padding: self.padding.clone(),
+                mode: self.mode,
+            })
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(pad(output, self.padding.clone(), self.mode))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| {
+            Expr::new(PadGradient {
+                expr: t.clone(),
+                padding: self.padding.clone(),
+                mode: self.mode,
+            })
+        })
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for PadGradient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pad_gradient({}, {:?}, {})",
+            self.expr, self.padding, self.mode
+        )
+    }
+}
+
+pub fn pad<V: Into<Expr>>(expr: V, padding: Vec<(usize, usize)>, mode: PadMode) -> Expr {
+    let expr = expr.into();
+    let shape = expr.shape();
+    for (&len, &(before, after)) in shape.slice().iter().zip(padding.iter()) {
+        match mode {
+            PadMode::Constant(_) => {}
+            PadMode::Reflect => {
+                if (before > 0 && before >= len) || (after > 0 && after >= len) {
+                    panic!("reflect padding must be less than the axis length");
+                }
+            }
+            PadMode::Replicate => {
+                if len == 0 && before + after > 0 {
+                    panic!("replicate padding requires a non-empty axis");
+                }
+            }
+        }
+    }
+    Expr::new(Pad {
+        expr,
+        padding,
+        mode,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[1.0, 2.0, 3.0]))),
+        );
+        let weights = expr(ndarray::arr1(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]));
+
+        let y = pad(x.clone(), vec![(2, 2)], PadMode::Constant(-1.0));
+        assert_eq!(
+            y.eval(),
+            ndarray::arr1(&[-1.0, -1.0, 1.0, 2.0, 3.0, -1.0, -1.0]).into_dyn()
+        );
+        assert_eq!(
+            (y * weights.clone()).gradient("x").eval(),
+            ndarray::arr1(&[3.0, 4.0, 5.0]).into_dyn()
+        );
+
+        let y = pad(x.clone(), vec![(2, 2)], PadMode::Reflect);
+        assert_eq!(
+            y.eval(),
+            ndarray::arr1(&[3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0]).into_dyn()
+        );
+        assert_eq!(
+            (y * weights.clone()).gradient("x").eval(),
+            ndarray::arr1(&[10.0, 12.0, 6.0]).into_dyn()
+        );
+
+        let y = pad(x.clone(), vec![(2, 2)], PadMode::Replicate);
+        assert_eq!(
+            y.eval(),
+            ndarray::arr1(&[1.0, 1.0, 1.0, 2.0, 3.0, 3.0, 3.0]).into_dyn()
+        );
+        assert_eq!(
+            (y * weights.clone()).gradient("x").eval(),
+            ndarray::arr1(&[6.0, 4.0, 18.0]).into_dyn()
+        );
+
+        let x = expr(ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]));
+        assert_eq!(
+            pad(x, vec![(1, 0), (0, 1)], PadMode::Constant(0.0)).eval(),
+            ndarray::arr2(&[[0.0, 0.0, 0.0], [1.0, 2.0, 0.0], [3.0, 4.0, 0.0]]).into_dyn()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_replicate_empty_axis() {
+        pad(
+            expr(ndarray::Array::zeros(ndarray::IxDyn(&[0]))),
+            vec![(1, 1)],
+            PadMode::Replicate,
+        );
+    }
+
+    #[test]
+    fn test_empty_axis() {
+        // axes without padding don't need to be long enough to pad
+        let x = expr(ndarray::Array::zeros(ndarray::IxDyn(&[0, 2])));
+        for &mode in [PadMode::Reflect, PadMode::Replicate].iter() {
+            assert_eq!(
+                pad(x.clone(), vec![(0, 0), (1, 1)], mode).eval(),
+                ndarray::Array::zeros(ndarray::IxDyn(&[0, 4]))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reflect_too_much() {
+        pad(
+            expr(ndarray::arr1(&[1.0, 2.0])),
+            vec![(2, 0)],
+            PadMode::Reflect,
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+// Permute reorders the axes of its input. Axis i of the output is axis axes[i] of the input.
+pub struct Permute {
+    pub expr: Expr,
+    pub axes: Vec<usize>,
+}
+
+impl ExprImpl for Permute {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let mut result = ndarray::Array::zeros(self.shape());
+        result.assign(&inputs[0].view().permuted_axes(ndarray::IxDyn(&self.axes)));
+        result
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let shape = self.expr.shape();
+        ndarray::IxDyn(
+            &self
+                .axes
+                .iter()
+                .map(|&axis| shape[axis])
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            transpose_axes(self.expr.propagate_constants(), self.axes.clone())
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        let mut inverse = vec![0; self.axes.len()];
+        for (i, &axis) in self.axes.iter().enumerate() {
+            inverse[axis] = i;
+        }
+        vec![Some(transpose_axes(output, inverse))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| transpose_axes(t.clone(), self.axes.clone()))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Permute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transpose_axes({}, {:?})", self.expr, self.axes)
+    }
+}
+
+// Reorders the axes of the expression according to the given permutation. For example, [0, 3, 1, 2]
+// converts NHWC to NCHW.
+pub fn transpose_axes<V: Into<Expr>>(expr: V, axes: Vec<usize>) -> Expr {
+    let expr = expr.into();
+    let mut sorted = axes.clone();
+    sorted.sort();
+    if sorted != (0..expr.shape().ndim()).collect::<Vec<_>>() {
+        panic!(
+            "invalid permutation {:?} for expression of shape {:?}",
+            axes,
+            expr.shape()
+        );
+    }
+    Expr::new(Permute { expr, axes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(
+                ndarray::Array::range(0.0, 6.0, 1.0)
+                    .into_shape((1, 2, 3))
+                    .unwrap(),
+            )),
+        );
+        let y = transpose_axes(x.clone(), vec![2, 0, 1]);
+        assert_eq!(y.shape(), ndarray::IxDyn(&[3, 1, 2]));
+        assert_eq!(
+            y.eval(),
+            ndarray::arr3(&[[[0.0, 3.0]], [[1.0, 4.0]], [[2.0, 5.0]]]).into_dyn()
+        );
+        assert_eq!(
+            y.reshape(ndarray::Ix1(6)).eval(),
+            ndarray::arr1(&[0.0, 3.0, 1.0, 4.0, 2.0, 5.0]).into_dyn()
+        );
+
+        let weights = ndarray::Array::range(1.0, 7.0, 1.0)
+            .into_shape((3, 1, 2))
+            .unwrap();
+        assert_eq!(
+            (y * expr(weights)).gradient("x").eval(),
+            ndarray::arr3(&[[[1.0, 3.0, 5.0], [2.0, 4.0, 6.0]]]).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+// Pow raises base to the power of exponent element-wise. If the base and exponent are not the same
+// shape, one must be a scalar. The gradient with respect to the exponent is taken to be zero where
+// the base isn't positive.
+#[derive(Clone)]
+pub struct Pow {
+    pub base: Expr,
+    pub exponent: Expr,
+}
+
+impl ExprImpl for Pow {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let (base, exponent) = (&inputs[0], &inputs[1]);
+        if base.ndim() == 0 {
+            let base = *base.first().unwrap();
+            exponent.mapv(|e| base.powf(e))
+        } else if exponent.ndim() == 0 {
+            let exponent = *exponent.first().unwrap();
+            base.mapv(|b| b.powf(exponent))
+        } else {
+            let mut result = base.clone();
+            ndarray::Zip::from(&mut result)
+                .and(exponent)
+                .apply(|b, &e| *b = b.powf(e));
+            result
+        }
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let base = self.base.shape();
+        if base.ndim() != 0 {
+            base
+        } else {
+            self.exponent.shape()
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.base.is_constant() && self.exponent.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.base
+                .propagate_constants()
+                .pow(self.exponent.propagate_constants())
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![
+            Some(output.clone() * self.base_derivative()),
+            if self.exponent.is_constant() {
+                None
+            } else {
+                Some(output * self.exponent_derivative())
+            },
+        ]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        let base = tangents[0]
+            .as_ref()
+            .map(|t| t.clone() * self.base_derivative());
+        let exponent = tangents[1]
+            .as_ref()
+            .map(|t| t.clone() * self.exponent_derivative());
+        match (base, exponent) {
+            (Some(base), Some(exponent)) => Some(base + exponent),
+            (base, exponent) => base.or(exponent),
+        }
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.base, &self.exponent]
+    }
+}
+
+impl Pow {
+    fn base_derivative(&self) -> Expr {
+        self.exponent.clone() * self.base.pow(self.exponent.clone() - 1.0)
+    }
+
+    fn exponent_derivative(&self) -> Expr {
+        // ln is only evaluated where the base is positive so that no NaNs are produced
+        let positive = super::cmp(self.base.clone(), super::cmp::Op::Greater, super::expr(0.0));
+        Expr::new(self.clone()) * super::ternary(positive, self.base.clone(), super::expr(1.0)).ln()
+    }
+}
+
+impl fmt::Display for Pow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pow({}, {})", self.base, self.exponent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[0.0, 2.0, -3.0]))),
+        );
+        let y = x.pow(2.0);
+        assert_eq!(y.eval(), ndarray::arr1(&[0.0, 4.0, 9.0]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[0.0, 4.0, -6.0]).into_dyn()
+        );
+
+        let e_value = Rc::new(VariableValue::new(ndarray::arr0(3.0)));
+        let e = v("e", e_value.clone());
+        let y = x.pow(e.clone());
+        assert_eq!(y.eval(), ndarray::arr1(&[0.0, 8.0, -27.0]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[0.0, 12.0, 27.0]).into_dyn()
+        );
+        assert_eq!(
+            y.gradient("e").eval(),
+            ndarray::arr0(8.0 * 2.0f32.ln()).into_dyn()
+        );
+
+        let mut tangents = HashMap::new();
+        tangents.insert(e_value.id(), expr(1.0));
+        assert_eq!(
+            expr(2.0).pow(e).jvp(&tangents).eval(),
+            ndarray::arr0(8.0 * 2.0f32.ln()).into_dyn()
+        );
+    }
+}
@@ -48,6 +48,12 @@ impl ExprImpl for ReduceSum {
         vec![Some(super::broadcast_to(output, self.expr.shape()))]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| reduce_sum(t.clone(), self.axes.clone()))
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
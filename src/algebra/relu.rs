@@ -0,0 +1,178 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Relu outputs max(x, 0) element-wise in a single pass.
+pub struct Relu {
+    pub expr: Expr,
+}
+
+impl ExprImpl for Relu {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| if v > 0.0 { v } else { 0.0 })
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            relu(self.expr.propagate_constants())
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(relu_gradient(output, self.expr.clone(), 0.0))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| relu_gradient(t.clone(), self.expr.clone(), 0.0))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Relu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "relu({})", self.expr)
+    }
+}
+
+// ReluGradient masks the gradient of a relu or leaky relu: elements of gradient pass through where
+// expr is positive and are scaled by alpha elsewhere. It's also the tangent of the relu in forward
+// mode. The mask is piecewise constant, so expr receives no gradient.
+pub struct ReluGradient {
+    pub gradient: Expr,
+    pub expr: Expr,
+    pub alpha: f32,
+}
+
+impl ExprImpl for ReluGradient {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let mut result = inputs[0].clone();
+        ndarray::Zip::from(&mut result)
+            .and(&inputs[1])
+            .apply(|g, &x| {
+                if x <= 0.0 {
+                    *g *= self.alpha;
+                }
+            });
+        result
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.gradient.is_constant() && self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            relu_gradient(
+                self.gradient.propagate_constants(),
+                self.expr.propagate_constants(),
+                self.alpha,
+            )
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![
+            Some(relu_gradient(output, self.expr.clone(), self.alpha)),
+            None,
+        ]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| relu_gradient(t.clone(), self.expr.clone(), self.alpha))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.gradient, &self.expr]
+    }
+}
+
+impl fmt::Display for ReluGradient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "relu_gradient({}, {}, {})",
+            self.gradient, self.expr, self.alpha
+        )
+    }
+}
+
+pub fn relu<V: Into<Expr>>(expr: V) -> Expr {
+    Expr::new(Relu { expr: expr.into() })
+}
+
+// The gradient is broadcast to the shape of expr if it's a scalar.
+pub fn relu_gradient<G: Into<Expr>, V: Into<Expr>>(gradient: G, expr: V, alpha: f32) -> Expr {
+    let expr = expr.into();
+    let gradient = gradient.into();
+    let gradient = if gradient.shape() != expr.shape() {
+        super::broadcast_to(gradient, expr.shape())
+    } else {
+        gradient
+    };
+    Expr::new(ReluGradient {
+        gradient,
+        expr,
+        alpha,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr1(&[-1.0, 0.0, 2.0])));
+        let x = v("x", x_value.clone());
+        let y = relu(x.clone());
+        assert_eq!(y.eval(), ndarray::arr1(&[0.0, 0.0, 2.0]).into_dyn());
+        assert_eq!(
+            (y.clone() * 3.0).gradient("x").eval(),
+            ndarray::arr1(&[0.0, 0.0, 3.0]).into_dyn()
+        );
+
+        let mut tangents = HashMap::new();
+        tangents.insert(x_value.id(), expr(ndarray::arr1(&[1.0, 1.0, 1.0])));
+        assert_eq!(
+            y.jvp(&tangents).eval(),
+            ndarray::arr1(&[0.0, 0.0, 1.0]).into_dyn()
+        );
+
+        // the whole gradient is a single node
+        assert_eq!(
+            format!("{}", y.gradient("x")),
+            "relu_gradient([1, 1, 1], x, 0)"
+        );
+    }
+}
@@ -36,6 +36,10 @@ impl ExprImpl for Reshape {
         vec![Some(output.reshape(self.expr.shape()))]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.reshape(self.shape.clone()))
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
@@ -0,0 +1,118 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+#[derive(Clone)]
+pub enum RoundingMode {
+    Floor,
+    Ceil,
+    // Rounds half-way cases away from zero.
+    Nearest,
+}
+
+impl RoundingMode {
+    pub fn round(&self, v: f32) -> f32 {
+        match self {
+            RoundingMode::Floor => v.floor(),
+            RoundingMode::Ceil => v.ceil(),
+            RoundingMode::Nearest => v.round(),
+        }
+    }
+}
+
+impl fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RoundingMode::Floor => "floor",
+                RoundingMode::Ceil => "ceil",
+                RoundingMode::Nearest => "round",
+            }
+        )
+    }
+}
+
+// Round rounds each element to an integer. It's piecewise constant, so it has no gradient. Use it
+// with stop_gradient or custom_gradient for straight-through estimators.
+pub struct Round {
+    pub expr: Expr,
+    pub mode: RoundingMode,
+}
+
+impl ExprImpl for Round {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| self.mode.round(v))
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            Expr::new(Round {
+                expr: self.expr.propagate_constants(),
+                mode: self.mode.clone(),
+            })
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        _output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![None]
+    }
+
+    fn jvp_inputs(&self, _tangents: &[Option<Expr>]) -> Option<Expr> {
+        None
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.mode, self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[-1.5, -0.2, 0.5, 1.7]))),
+        );
+        assert_eq!(
+            x.floor().eval(),
+            ndarray::arr1(&[-2.0, -1.0, 0.0, 1.0]).into_dyn()
+        );
+        assert_eq!(
+            x.ceil().eval(),
+            ndarray::arr1(&[-1.0, -0.0, 1.0, 2.0]).into_dyn()
+        );
+        assert_eq!(
+            x.round().eval(),
+            ndarray::arr1(&[-2.0, -0.0, 1.0, 2.0]).into_dyn()
+        );
+        assert_eq!(
+            (x.floor() + x.ceil() + x.round()).gradients_wrt(std::slice::from_ref(&x))[0].eval(),
+            ndarray::arr1(&[0.0, 0.0, 0.0, 0.0]).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes the element-wise reciprocal of the square root of the expression.
+#[derive(Clone)]
+pub struct Rsqrt {
+    pub expr: Expr,
+}
+
+impl Rsqrt {
+    fn derivative(&self) -> Expr {
+        let y = Expr::new(self.clone());
+        -0.5 * y.clone() * y.clone() * y
+    }
+}
+
+impl ExprImpl for Rsqrt {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| 1.0 / v.sqrt())
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().rsqrt()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Rsqrt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rsqrt({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v("x", Rc::new(VariableValue::new(ndarray::arr1(&[1.0, 4.0]))));
+        let y = x.rsqrt();
+        assert_eq!(y.eval(), ndarray::arr1(&[1.0, 0.5]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[-0.5, -0.0625]).into_dyn()
+        );
+    }
+}
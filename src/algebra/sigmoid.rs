@@ -0,0 +1,91 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes the element-wise logistic sigmoid of the expression. Large magnitude inputs saturate
+// to 0 or 1 rather than overflowing.
+#[derive(Clone)]
+pub struct Sigmoid {
+    pub expr: Expr,
+}
+
+impl Sigmoid {
+    fn derivative(&self) -> Expr {
+        let y = Expr::new(self.clone());
+        y.clone() * (1.0 - y)
+    }
+}
+
+impl ExprImpl for Sigmoid {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| {
+            if v >= 0.0 {
+                1.0 / (1.0 + (-v).exp())
+            } else {
+                let e = v.exp();
+                e / (1.0 + e)
+            }
+        })
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().sigmoid()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Sigmoid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sigmoid({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[
+                0.0, 2.0, -100.0, 100.0,
+            ]))),
+        );
+        let y = x.sigmoid();
+        assert!(y
+            .eval()
+            .all_close(&ndarray::arr1(&[0.5, 0.8807971, 0.0, 1.0]).into_dyn(), 1e-6));
+        assert!(y.gradient("x").eval().all_close(
+            &ndarray::arr1(&[0.25, 0.10499359, 0.0, 0.0]).into_dyn(),
+            1e-6
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Sign outputs -1, 0, or 1 depending on the sign of each element. It's piecewise constant, so it has
+// no gradient.
+pub struct Sign {
+    pub expr: Expr,
+}
+
+impl ExprImpl for Sign {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| {
+            if v > 0.0 {
+                1.0
+            } else if v < 0.0 {
+                -1.0
+            } else {
+                0.0
+            }
+        })
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().sign()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        _output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![None]
+    }
+
+    fn jvp_inputs(&self, _tangents: &[Option<Expr>]) -> Option<Expr> {
+        None
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Sign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sign({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[-2.0, 0.0, 0.5]))),
+        );
+        let y = x.sign();
+        assert_eq!(y.eval(), ndarray::arr1(&[-1.0, 0.0, 1.0]).into_dyn());
+        assert_eq!(
+            y.gradients_wrt(std::slice::from_ref(&x))[0].eval(),
+            ndarray::arr1(&[0.0, 0.0, 0.0]).into_dyn()
+        );
+    }
+}
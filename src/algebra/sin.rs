@@ -0,0 +1,82 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes the element-wise sine of the expression.
+pub struct Sin {
+    pub expr: Expr,
+}
+
+impl Sin {
+    fn derivative(&self) -> Expr {
+        self.expr.cos()
+    }
+}
+
+impl ExprImpl for Sin {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| v.sin())
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().sin()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Sin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sin({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[
+                0.0,
+                std::f32::consts::PI,
+            ]))),
+        );
+        let y = x.sin();
+        assert!(y
+            .eval()
+            .all_close(&ndarray::arr1(&[0.0, 0.0]).into_dyn(), 1e-6));
+        assert!(y
+            .gradient("x")
+            .eval()
+            .all_close(&ndarray::arr1(&[1.0, -1.0]).into_dyn(), 1e-6));
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+fn slice_view<'a>(
+    mut view: ndarray::ArrayViewD<'a, f32>,
+    slices: &[ndarray::Slice],
+) -> ndarray::ArrayViewD<'a, f32> {
+    for (axis, &s) in slices.iter().enumerate() {
+        view.slice_axis_inplace(ndarray::Axis(axis), s);
+    }
+    view
+}
+
+fn sliced_shape(shape: ndarray::IxDyn, slices: &[ndarray::Slice]) -> ndarray::IxDyn {
+    if shape.ndim() != slices.len() {
+        panic!(
+            "incorrect number of slices. got {}, expected {}",
+            slices.len(),
+            shape.ndim()
+        );
+    }
+    // Slicing a broadcasted scalar lets ndarray do the math without allocating anything.
+    let scalar = ndarray::arr0(0.0).into_dyn();
+    slice_view(scalar.broadcast(shape).unwrap(), slices).raw_dim()
+}
+
+// Slice takes a sub-tensor of its input. There must be exactly one slice per axis, and each slice
+// can have its own start, end, and step. Negative indices and steps behave as they do in ndarray.
+pub struct Slice {
+    pub expr: Expr,
+    pub slices: Vec<ndarray::Slice>,
+}
+
+impl ExprImpl for Slice {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        slice_view(inputs[0].view(), &self.slices).to_owned()
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        sliced_shape(self.expr.shape(), &self.slices)
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            slice(self.expr.propagate_constants(), self.slices.clone())
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(Expr::new(SliceGradient {
+            expr: output,
+            shape: self.expr.shape(),
+            slices: self.slices.clone(),
+        }))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| slice(t.clone(), self.slices.clone()))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Slice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "slice({}, {:?})", self.expr, self.slices)
+    }
+}
+
+// SliceGradient is the inverse of Slice. It places its input into the sliced region of a larger
+// tensor of zeros.
+pub struct SliceGradient {
+    pub expr: Expr,
+    pub shape: ndarray::IxDyn,
+    pub slices: Vec<ndarray::Slice>,
+}
+
+impl ExprImpl for SliceGradient {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let mut result = ndarray::Array::zeros(self.shape.clone());
+        {
+            let mut view = result.view_mut();
+            for (axis, &s) in self.slices.iter().enumerate() {
+                view.slice_axis_inplace(ndarray::Axis(axis), s);
+            }
+            view.assign(&inputs[0]);
+        }
+        result
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.shape.clone()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            Expr::new(SliceGradient {
+                expr: self.expr.propagate_constants(),
+                shape: self.shape.clone(),
+                slices: self.slices.clone(),
+            })
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(slice(output, self.slices.clone()))]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| {
+            Expr::new(SliceGradient {
+                expr: t.clone(),
+                shape: self.shape.clone(),
+                slices: self.slices.clone(),
+            })
+        })
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for SliceGradient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "slice_gradient({}, {:?}, {:?})",
+            self.expr, self.shape, self.slices
+        )
+    }
+}
+
+pub fn slice<V: Into<Expr>>(expr: V, slices: Vec<ndarray::Slice>) -> Expr {
+    Expr::new(Slice {
+        expr: expr.into(),
+        slices,
+    })
+}
+
+// Splits the expression into consecutive pieces along the given axis. The sizes must add up to the
+// length of the axis.
+pub fn split<V: Into<Expr>>(expr: V, axis: usize, sizes: &[usize]) -> Vec<Expr> {
+    let expr = expr.into();
+    let shape = expr.shape();
+    if sizes.iter().sum::<usize>() != shape[axis] {
+        panic!(
+            "split sizes {:?} don't add up to the length of axis {} of {:?}",
+            sizes, axis, shape
+        );
+    }
+    let mut offset = 0;
+    sizes
+        .iter()
+        .map(|&size| {
+            let mut slices = vec![ndarray::Slice::from(..); shape.ndim()];
+            slices[axis] = ndarray::Slice::from(offset..offset + size);
+            offset += size;
+            slice(expr.clone(), slices)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr2(&[
+                [0.0, 1.0, 2.0],
+                [3.0, 4.0, 5.0],
+            ]))),
+        );
+        let y = slice(
+            x.clone(),
+            vec![ndarray::Slice::from(1..), ndarray::Slice::new(0, None, 2)],
+        );
+        assert_eq!(y.shape(), ndarray::IxDyn(&[1, 2]));
+        assert_eq!(y.eval(), ndarray::arr2(&[[3.0, 5.0]]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr2(&[[0.0, 0.0, 0.0], [1.0, 0.0, 1.0]]).into_dyn()
+        );
+
+        let y = slice(
+            x.clone(),
+            vec![ndarray::Slice::from(..), ndarray::Slice::new(0, None, -1)],
+        );
+        assert_eq!(
+            y.eval(),
+            ndarray::arr2(&[[2.0, 1.0, 0.0], [5.0, 4.0, 3.0]]).into_dyn()
+        );
+        assert_eq!(
+            (y * expr(ndarray::arr2(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]])))
+                .gradient("x")
+                .eval(),
+            ndarray::arr2(&[[3.0, 2.0, 1.0], [6.0, 5.0, 4.0]]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[0.0, 1.0, 2.0, 3.0]))),
+        );
+        let pieces = split(x.clone(), 0, &[1, 3]);
+        assert_eq!(pieces[0].eval(), ndarray::arr1(&[0.0]).into_dyn());
+        assert_eq!(pieces[1].eval(), ndarray::arr1(&[1.0, 2.0, 3.0]).into_dyn());
+        assert_eq!(
+            (pieces[0].clone() * 2.0 + pieces[1].sum())
+                .gradient("x")
+                .eval(),
+            ndarray::arr1(&[2.0, 1.0, 1.0, 1.0]).into_dyn()
+        );
+    }
+}
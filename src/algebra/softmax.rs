@@ -40,6 +40,13 @@ impl ExprImpl for Softmax {
         )]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| {
+            let softmax = softmax(self.expr.clone());
+            (t.clone() - (t.clone() * softmax.clone()).sum()) * softmax
+        })
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes ln(1 + exp(x)) element-wise without overflowing for large inputs.
+pub struct Softplus {
+    pub expr: Expr,
+}
+
+impl Softplus {
+    fn derivative(&self) -> Expr {
+        self.expr.sigmoid()
+    }
+}
+
+impl ExprImpl for Softplus {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| v.max(0.0) + (-v.abs()).exp().ln_1p())
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().softplus()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Softplus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "softplus({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[
+                -100.0, -1.0, 0.0, 2.0, 100.0,
+            ]))),
+        );
+        let y = x.softplus();
+        assert!(y.eval().all_close(
+            &ndarray::arr1(&[0.0, 0.313_261_7, std::f32::consts::LN_2, 2.126_928, 100.0])
+                .into_dyn(),
+            1e-6
+        ));
+        assert!(y.gradient("x").eval().all_close(
+            &ndarray::arr1(&[0.0, 0.268_941_4, 0.5, 0.880_797_1, 1.0]).into_dyn(),
+            1e-6
+        ));
+    }
+}
@@ -36,6 +36,12 @@ impl ExprImpl for Sqrt {
         vec![Some(output.clone() * 0.5 / self.expr.sqrt())]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| t.clone() * 0.5 / self.expr.sqrt())
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
@@ -35,6 +35,12 @@ impl ExprImpl for Square {
         vec![Some(output.clone() * 2.0 * self.expr.clone())]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| t.clone() * 2.0 * self.expr.clone())
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
@@ -0,0 +1,104 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// StopGradient evaluates to its input, but is treated as a constant for the purpose of
+// differentiation. Nothing is propagated through it in either direction.
+pub struct StopGradient {
+    pub expr: Expr,
+}
+
+impl ExprImpl for StopGradient {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].clone()
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            stop_gradient(self.expr.propagate_constants())
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        _output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![None]
+    }
+
+    fn jvp_inputs(&self, _tangents: &[Option<Expr>]) -> Option<Expr> {
+        None
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for StopGradient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stop_gradient({})", self.expr)
+    }
+}
+
+pub fn stop_gradient<V: Into<Expr>>(expr: V) -> Expr {
+    Expr::new(StopGradient { expr: expr.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::graph;
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr1(&[1.0, 2.0])));
+        let x = v("x", x_value.clone());
+        let y = x.square() + stop_gradient(x.square());
+        assert_eq!(y.eval(), ndarray::arr1(&[2.0, 8.0]).into_dyn());
+        assert_eq!(
+            y.gradient("x").eval(),
+            ndarray::arr1(&[2.0, 4.0]).into_dyn()
+        );
+        assert_eq!(
+            stop_gradient(x.clone()).gradient("x").eval(),
+            ndarray::arr0(0.0).into_dyn()
+        );
+
+        let mut tangents = HashMap::new();
+        tangents.insert(x_value.id(), expr(ndarray::arr1(&[1.0, 1.0])));
+        assert_eq!(
+            y.jvp(&tangents).eval(),
+            ndarray::arr1(&[2.0, 4.0]).into_dyn()
+        );
+
+        // straight-through estimator: rounds on the way forward, but acts as the identity on the
+        // way backward
+        let round = expr(ndarray::arr1(&[1.0, 2.0]));
+        let y = x.clone() + stop_gradient(round - x.clone());
+        let mut graph = graph::Graph::new();
+        let output = graph.add(y.clone());
+        let gradient = graph.add(y.gradients_wrt(&[x])[0].clone());
+        x_value.set(ndarray::arr1(&[1.2, 1.7]));
+        graph.eval();
+        assert_eq!(
+            graph.node_output(output),
+            &ndarray::arr1(&[1.0, 2.0]).into_dyn()
+        );
+        assert_eq!(
+            graph.node_output(gradient),
+            &ndarray::arr1(&[1.0, 1.0]).into_dyn()
+        );
+    }
+}
@@ -56,6 +56,15 @@ impl ExprImpl for Sub {
         vec![Some(output.clone()), Some(-1.0 * output.clone())]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        match (&tangents[0], &tangents[1]) {
+            (Some(left), Some(right)) => Some(left.clone() - right.clone()),
+            (Some(left), None) => Some(left.clone()),
+            (None, Some(right)) => Some(-1.0 * right.clone()),
+            (None, None) => None,
+        }
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.left, &self.right]
     }
@@ -77,6 +86,16 @@ impl<T: Into<Expr>> std::ops::Sub<T> for Expr {
     }
 }
 
+impl std::ops::Sub<Expr> for f32 {
+    type Output = Expr;
+    fn sub(self, rhs: Expr) -> Expr {
+        Expr::new(Sub {
+            left: super::expr(self),
+            right: rhs,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::*;
@@ -40,6 +40,10 @@ impl ExprImpl for Sum {
         )]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.sum())
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
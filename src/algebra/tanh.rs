@@ -0,0 +1,81 @@
+use std::fmt;
+
+use super::{Expr, ExprImpl};
+
+// Computes the element-wise hyperbolic tangent of the expression.
+#[derive(Clone)]
+pub struct Tanh {
+    pub expr: Expr,
+}
+
+impl Tanh {
+    fn derivative(&self) -> Expr {
+        let y = Expr::new(self.clone());
+        1.0 - y.square()
+    }
+}
+
+impl ExprImpl for Tanh {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        inputs[0].mapv(|v| v.tanh())
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        self.expr.shape()
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            self.expr.propagate_constants().tanh()
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        vec![Some(output * self.derivative())]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.clone() * self.derivative())
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Tanh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tanh({})", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr1(&[0.0, 1.0, -20.0]))),
+        );
+        let y = x.tanh();
+        assert!(y
+            .eval()
+            .all_close(&ndarray::arr1(&[0.0, 0.7615942, -1.0]).into_dyn(), 1e-6));
+        assert!(y
+            .gradient("x")
+            .eval()
+            .all_close(&ndarray::arr1(&[1.0, 0.41997434, 0.0]).into_dyn(), 1e-6));
+    }
+}
@@ -99,6 +99,18 @@ impl ExprImpl for Ternary {
         ]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        if tangents[1].is_none() && tangents[2].is_none() {
+            return None;
+        }
+        let zero = || super::expr(0.0);
+        Some(ternary(
+            self.condition.clone(),
+            tangents[1].clone().unwrap_or_else(zero),
+            tangents[2].clone().unwrap_or_else(zero),
+        ))
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.condition, &self.true_expr, &self.false_expr]
     }
@@ -134,4 +146,16 @@ mod tests {
         let x = expr(ndarray::arr1(&[3.0, 2.0]));
         assert_eq!(ternary(c, t, f).eval(), x.eval());
     }
+
+    #[test]
+    fn test_jvp() {
+        let x_value = Rc::new(VariableValue::new(ndarray::arr1(&[-1.0, 2.0])));
+        let x = v("x", x_value.clone());
+        let mut tangents = HashMap::new();
+        tangents.insert(x_value.id(), expr(ndarray::arr1(&[1.0, 1.0])));
+        assert_eq!(
+            x.max(expr(0.0)).jvp(&tangents).eval(),
+            ndarray::arr1(&[0.0, 1.0]).into_dyn()
+        );
+    }
 }
@@ -0,0 +1,150 @@
+use std::fmt;
+
+use ndarray::Dimension;
+
+use super::{Expr, ExprImpl};
+
+// Tile repeats its input along each axis the given number of times.
+pub struct Tile {
+    pub expr: Expr,
+    pub reps: Vec<usize>,
+}
+
+impl ExprImpl for Tile {
+    fn eval_inputs(&self, inputs: &Vec<ndarray::ArrayD<f32>>) -> ndarray::ArrayD<f32> {
+        let mut result = inputs[0].clone();
+        for (axis, &reps) in self.reps.iter().enumerate() {
+            if reps == 0 {
+                // stack can't take an empty list, so build the empty axis directly
+                let mut shape = result.raw_dim();
+                shape[axis] = 0;
+                result = ndarray::Array::zeros(shape);
+                continue;
+            }
+            let views = vec![result.view(); reps];
+            result = ndarray::stack(ndarray::Axis(axis), &views).unwrap();
+        }
+        result
+    }
+
+    fn shape(&self) -> ndarray::IxDyn {
+        let mut shape = self.expr.shape();
+        if shape.ndim() != self.reps.len() {
+            panic!(
+                "incorrect number of repetitions for tile. got {}, expected {}",
+                self.reps.len(),
+                shape.ndim()
+            );
+        }
+        for (axis, &reps) in self.reps.iter().enumerate() {
+            shape[axis] *= reps;
+        }
+        shape
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn propagate_constants(&self) -> Expr {
+        if self.is_constant() {
+            super::expr(self.eval())
+        } else {
+            tile(self.expr.propagate_constants(), self.reps.clone())
+        }
+    }
+
+    fn accumulate_gradients(
+        &self,
+        output: Expr,
+        _gradients: &mut super::Gradients,
+    ) -> Vec<Option<Expr>> {
+        let shape = self.expr.shape();
+        if self.reps.contains(&0) {
+            // nothing was copied, and reducing an empty axis would panic
+            return vec![Some(super::expr(ndarray::Array::zeros(shape)))];
+        }
+
+        // Split each axis into (repetition, element) and sum over the repetitions.
+        let mut split_shape = Vec::new();
+        let mut reduction_axes = Vec::new();
+        for (axis, &reps) in self.reps.iter().enumerate() {
+            reduction_axes.push(split_shape.len());
+            split_shape.push(reps);
+            split_shape.push(shape[axis]);
+        }
+        vec![Some(
+            super::reduce_sum(output.reshape(ndarray::IxDyn(&split_shape)), reduction_axes)
+                .reshape(shape),
+        )]
+    }
+
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0]
+            .as_ref()
+            .map(|t| tile(t.clone(), self.reps.clone()))
+    }
+
+    fn inputs(&self) -> Vec<&Expr> {
+        vec![&self.expr]
+    }
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tile({}, {:?})", self.expr, self.reps)
+    }
+}
+
+pub fn tile<V: Into<Expr>>(expr: V, reps: Vec<usize>) -> Expr {
+    Expr::new(Tile {
+        expr: expr.into(),
+        reps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test() {
+        let x = v(
+            "x",
+            Rc::new(VariableValue::new(ndarray::arr2(&[[0.0, 1.0], [2.0, 3.0]]))),
+        );
+        let y = tile(x.clone(), vec![2, 1]);
+        assert_eq!(
+            y.eval(),
+            ndarray::arr2(&[[0.0, 1.0], [2.0, 3.0], [0.0, 1.0], [2.0, 3.0]]).into_dyn()
+        );
+        assert_eq!(
+            (y * expr(ndarray::arr2(&[
+                [1.0, 2.0],
+                [3.0, 4.0],
+                [5.0, 6.0],
+                [7.0, 8.0]
+            ])))
+            .gradient("x")
+            .eval(),
+            ndarray::arr2(&[[6.0, 8.0], [10.0, 12.0]]).into_dyn()
+        );
+        assert_eq!(
+            tile(x.clone(), vec![1, 3]).eval(),
+            ndarray::arr2(&[
+                [0.0, 1.0, 0.0, 1.0, 0.0, 1.0],
+                [2.0, 3.0, 2.0, 3.0, 2.0, 3.0]
+            ])
+            .into_dyn()
+        );
+
+        // zero repetitions leave an empty axis
+        let y = tile(x.clone(), vec![0, 2]);
+        assert_eq!(y.eval().shape(), &[0, 4]);
+        assert_eq!(y.shape(), ndarray::IxDyn(&[0, 4]));
+        assert_eq!(
+            y.sum().gradient("x").eval(),
+            ndarray::Array::zeros(ndarray::IxDyn(&[2, 2]))
+        );
+    }
+}
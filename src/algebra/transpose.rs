@@ -39,6 +39,10 @@ impl ExprImpl for Transpose {
         vec![Some(output.transpose())]
     }
 
+    fn jvp_inputs(&self, tangents: &[Option<Expr>]) -> Option<Expr> {
+        tangents[0].as_ref().map(|t| t.transpose())
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![&self.expr]
     }
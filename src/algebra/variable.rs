@@ -2,10 +2,15 @@ use std::cell::RefCell;
 use std::fmt;
 use std::ops::DerefMut;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use super::{Expr, ExprImpl};
 
-pub struct VariableValue(RefCell<ndarray::ArrayD<f32>>);
+static GLOBAL_VARIABLE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// VariableValue holds the value of a variable. Its id is what identifies the variable for the
+// purpose of differentiation. The name given to the variable is only used for display.
+pub struct VariableValue(RefCell<ndarray::ArrayD<f32>>, usize);
 
 impl VariableValue {
     pub fn new<S, D>(a: ndarray::ArrayBase<S, D>) -> VariableValue
@@ -13,7 +18,15 @@ impl VariableValue {
         S: ndarray::Data<Elem = f32>,
         D: ndarray::Dimension,
     {
-        VariableValue(RefCell::new(a.into_owned().into_dyn()))
+        VariableValue(
+            RefCell::new(a.into_owned().into_dyn()),
+            GLOBAL_VARIABLE_COUNT.fetch_add(1, Ordering::SeqCst),
+        )
+    }
+
+    // Returns a process-wide unique identifier for this value.
+    pub fn id(&self) -> usize {
+        self.1
     }
 
     pub fn set<S, D>(&self, a: ndarray::ArrayBase<S, D>)
@@ -69,16 +82,28 @@ impl ExprImpl for Variable {
         output: Expr,
         gradients: &mut super::Gradients,
     ) -> Vec<Option<Expr>> {
+        let id = self.value.id();
         gradients.expressions.insert(
-            self.name.clone(),
-            match gradients.expressions.get(&self.name) {
+            id,
+            match gradients.expressions.get(&id) {
                 Some(grad) => grad.clone() + output,
                 None => output,
             },
         );
+        gradients.names.insert(id, self.name.clone());
         vec![]
     }
 
+    fn as_variable(&self) -> Option<&Variable> {
+        Some(self)
+    }
+
+    fn jvp_inputs(&self, _tangents: &[Option<Expr>]) -> Option<Expr> {
+        // Variables are the roots of forward-mode differentiation. Their tangents are looked up by
+        // Expr::jvp rather than computed here.
+        None
+    }
+
     fn inputs(&self) -> Vec<&Expr> {
         vec![]
     }
@@ -112,4 +137,36 @@ mod tests {
             ndarray::arr1(&[1.0, 1.0, 1.0]).into_dyn()
         );
     }
+
+    #[test]
+    fn test_identity() {
+        let x1 = v("x", Rc::new(VariableValue::new(ndarray::arr0(0.0))));
+        let x2 = v("x", Rc::new(VariableValue::new(ndarray::arr0(0.0))));
+        let y = 2.0 * x1.clone() + 3.0 * x2.clone();
+        let gradients = y.gradients_wrt(&[x2.clone(), x1.clone()]);
+        assert_eq!(gradients[0].eval(), ndarray::arr0(3.0).into_dyn());
+        assert_eq!(gradients[1].eval(), ndarray::arr0(2.0).into_dyn());
+
+        let value = Rc::new(VariableValue::new(ndarray::arr0(3.0)));
+        let x = v("x", value.clone());
+        let y = x.clone() * v("x", value.clone());
+        assert_eq!(
+            y.gradients_wrt(&[x])[0].eval(),
+            ndarray::arr0(6.0).into_dyn()
+        );
+
+        let z = v("z", Rc::new(VariableValue::new(ndarray::Array::zeros(2))));
+        assert_eq!(
+            y.gradients_wrt(&[z])[0].eval(),
+            ndarray::arr1(&[0.0, 0.0]).into_dyn()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ambiguous_name() {
+        let x1 = v("x", Rc::new(VariableValue::new(ndarray::arr0(0.0))));
+        let x2 = v("x", Rc::new(VariableValue::new(ndarray::arr0(0.0))));
+        (x1 + x2).gradient("x");
+    }
 }
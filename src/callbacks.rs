@@ -0,0 +1,551 @@
+use std::error::Error;
+use std::io::Write;
+
+use super::metrics::Logs;
+use super::models::CompiledTrainingSequential;
+
+// Callbacks are invoked by fit as training progresses. They can inspect the logs and adjust the
+// model, e.g. to change its learning rate or stop training.
+pub trait Callback {
+    fn on_train_begin(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_epoch_begin(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+        _epoch: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // The logs contain the loss averaged over the epoch so far, but not the metrics, which are
+    // only computed at the end of each epoch. If the batch completed a step, they also contain the
+    // global norm of the step's gradients before clipping as "gradient_norm".
+    fn on_batch_end(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+        _batch: usize,
+        _logs: &Logs,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // The logs contain the loss and metrics for the epoch, including validation results.
+    fn on_epoch_end(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+        _epoch: usize,
+        _logs: &Logs,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // This is called once on_train_begin has succeeded for every callback, even if training then
+    // fails, so that callbacks can clean up.
+    fn on_train_end(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+// History is returned by fit and contains the logs of each epoch.
+#[derive(Clone, Default)]
+pub struct History {
+    pub epochs: Vec<Logs>,
+}
+
+impl History {
+    // Returns the value of a metric for each epoch that has it.
+    pub fn get(&self, metric: &str) -> Vec<f32> {
+        self.epochs
+            .iter()
+            .filter_map(|logs| logs.get(metric).cloned())
+            .collect()
+    }
+}
+
+// Mode determines whether a monitored metric improves by decreasing, like losses, or increasing,
+// like accuracy.
+#[derive(Clone, Copy)]
+pub enum Mode {
+    Min,
+    Max,
+}
+
+// Monitor keeps track of the best value of a metric.
+struct Monitor {
+    metric: String,
+    mode: Mode,
+    best: Option<f32>,
+}
+
+impl Monitor {
+    fn new(metric: &str, mode: Mode) -> Monitor {
+        Monitor {
+            metric: metric.to_string(),
+            mode,
+            best: None,
+        }
+    }
+
+    // Records the metric's value for an epoch, returning true if it's the best so far.
+    fn update(&mut self, logs: &Logs) -> Result<bool, Box<dyn Error>> {
+        let value = match logs.get(&self.metric) {
+            Some(&v) => v,
+            None => bail!("metric not found: {}", self.metric),
+        };
+        let improved = match (self.best, self.mode) {
+            (None, _) => true,
+            (Some(best), Mode::Min) => value < best,
+            (Some(best), Mode::Max) => value > best,
+        };
+        if improved {
+            self.best = Some(value);
+        }
+        Ok(improved)
+    }
+}
+
+// Stops training once the monitored metric hasn't improved for a number of epochs. Optionally,
+// the weights from the best epoch are restored when training ends.
+pub struct EarlyStopping {
+    monitor: Monitor,
+    patience: usize,
+    restore_best_weights: bool,
+    wait: usize,
+    best_weights: Vec<(String, ndarray::ArrayD<f32>)>,
+}
+
+impl EarlyStopping {
+    pub fn new(
+        metric: &str,
+        mode: Mode,
+        patience: usize,
+        restore_best_weights: bool,
+    ) -> EarlyStopping {
+        EarlyStopping {
+            monitor: Monitor::new(metric, mode),
+            patience,
+            restore_best_weights,
+            wait: 0,
+            best_weights: Vec::new(),
+        }
+    }
+}
+
+impl Callback for EarlyStopping {
+    fn on_train_begin(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        self.monitor.best = None;
+        self.wait = 0;
+        self.best_weights.clear();
+        Ok(())
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        model: &mut CompiledTrainingSequential,
+        epoch: usize,
+        logs: &Logs,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.monitor.update(logs)? {
+            self.wait = 0;
+            if self.restore_best_weights {
+                self.best_weights = model.get_weights();
+            }
+            return Ok(());
+        }
+        self.wait += 1;
+        if self.wait >= self.patience {
+            info!("stopping early at epoch {}", epoch);
+            model.stop_training();
+        }
+        Ok(())
+    }
+
+    // The best weights are restored whether training stopped early or ran out of epochs.
+    fn on_train_end(
+        &mut self,
+        model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.restore_best_weights && self.wait > 0 {
+            model.set_weights(&self.best_weights)?;
+        }
+        Ok(())
+    }
+}
+
+// Saves the model's weights at the end of each epoch, or only when the monitored metric improves.
+pub struct ModelCheckpoint {
+    path: std::path::PathBuf,
+    monitor: Monitor,
+    save_best_only: bool,
+}
+
+impl ModelCheckpoint {
+    pub fn new<P: Into<std::path::PathBuf>>(
+        path: P,
+        metric: &str,
+        mode: Mode,
+        save_best_only: bool,
+    ) -> ModelCheckpoint {
+        ModelCheckpoint {
+            path: path.into(),
+            monitor: Monitor::new(metric, mode),
+            save_best_only,
+        }
+    }
+}
+
+impl Callback for ModelCheckpoint {
+    fn on_train_begin(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        self.monitor.best = None;
+        Ok(())
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        model: &mut CompiledTrainingSequential,
+        _epoch: usize,
+        logs: &Logs,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.monitor.update(logs)? || !self.save_best_only {
+            model.save_weights(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+// Multiplies the learning rate by a factor once the monitored metric hasn't improved for a number
+// of epochs, down to a minimum.
+pub struct ReduceLROnPlateau {
+    monitor: Monitor,
+    factor: f32,
+    patience: usize,
+    min_learning_rate: f32,
+    wait: usize,
+}
+
+impl ReduceLROnPlateau {
+    pub fn new(
+        metric: &str,
+        mode: Mode,
+        factor: f32,
+        patience: usize,
+        min_learning_rate: f32,
+    ) -> ReduceLROnPlateau {
+        ReduceLROnPlateau {
+            monitor: Monitor::new(metric, mode),
+            factor,
+            patience,
+            min_learning_rate,
+            wait: 0,
+        }
+    }
+}
+
+impl Callback for ReduceLROnPlateau {
+    fn on_train_begin(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        self.monitor.best = None;
+        self.wait = 0;
+        Ok(())
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        model: &mut CompiledTrainingSequential,
+        _epoch: usize,
+        logs: &Logs,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.monitor.update(logs)? {
+            self.wait = 0;
+            return Ok(());
+        }
+        self.wait += 1;
+        if self.wait >= self.patience {
+            let learning_rate = (model.learning_rate() * self.factor).max(self.min_learning_rate);
+            info!("reducing learning rate to {}", learning_rate);
+            model.set_learning_rate(learning_rate);
+            self.wait = 0;
+        }
+        Ok(())
+    }
+}
+
+// Writes the logs of each epoch to a CSV file. The columns are determined by the first epoch.
+pub struct CSVLogger {
+    path: std::path::PathBuf,
+    file: Option<std::io::BufWriter<std::fs::File>>,
+    columns: Vec<String>,
+}
+
+impl CSVLogger {
+    pub fn new<P: Into<std::path::PathBuf>>(path: P) -> CSVLogger {
+        CSVLogger {
+            path: path.into(),
+            file: None,
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl Callback for CSVLogger {
+    fn on_train_begin(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        self.file = Some(std::io::BufWriter::new(std::fs::File::create(&self.path)?));
+        self.columns.clear();
+        Ok(())
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+        epoch: usize,
+        logs: &Logs,
+    ) -> Result<(), Box<dyn Error>> {
+        let file = match self.file.as_mut() {
+            Some(f) => f,
+            None => bail!("csv logger was not started"),
+        };
+        if self.columns.is_empty() {
+            self.columns = logs.keys().cloned().collect();
+            writeln!(file, "epoch,{}", self.columns.join(","))?;
+        }
+        let mut line = epoch.to_string();
+        for column in self.columns.iter() {
+            line.push(',');
+            if let Some(v) = logs.get(column) {
+                line += &v.to_string();
+            }
+        }
+        writeln!(file, "{}", line)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    fn on_train_end(
+        &mut self,
+        _model: &mut CompiledTrainingSequential,
+    ) -> Result<(), Box<dyn Error>> {
+        self.file = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{bias_model, BatchLogsRecorder, TestDataset};
+    use super::super::{clipping, metrics};
+    use super::*;
+
+    // With this learning rate, each step overshoots the target by more than the last, so the loss
+    // is best after the first epoch, when the output is [2.5, 0.0].
+    fn diverging_dataset() -> TestDataset {
+        TestDataset::new(&[[1.0, 0.0]])
+    }
+    const DIVERGING_LEARNING_RATE: f32 = 2.5;
+
+    fn predict(model: &mut CompiledTrainingSequential) -> ndarray::ArrayD<f32> {
+        model.predict(ndarray::arr1(&[0.0, 0.0])).clone()
+    }
+
+    // Fails at the end of the given epoch.
+    struct FailingCallback {
+        epoch: usize,
+    }
+
+    impl Callback for FailingCallback {
+        fn on_epoch_end(
+            &mut self,
+            _model: &mut CompiledTrainingSequential,
+            epoch: usize,
+            _logs: &Logs,
+        ) -> Result<(), Box<dyn Error>> {
+            if epoch == self.epoch {
+                bail!("failing at epoch {}", epoch);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let mut model = bias_model();
+        let mut callbacks: Vec<Box<dyn Callback>> =
+            vec![Box::new(EarlyStopping::new("loss", Mode::Min, 2, false))];
+        let history = model
+            .fit(
+                &mut diverging_dataset(),
+                DIVERGING_LEARNING_RATE,
+                10,
+                None,
+                &mut callbacks,
+            )
+            .unwrap();
+        assert_eq!(history.epochs.len(), 3);
+        assert_eq!(history.get("loss"), vec![0.5, 1.125, 2.53125]);
+
+        let mut model = bias_model();
+        let mut callbacks: Vec<Box<dyn Callback>> =
+            vec![Box::new(EarlyStopping::new("loss", Mode::Min, 1, true))];
+        let history = model
+            .fit(
+                &mut diverging_dataset(),
+                DIVERGING_LEARNING_RATE,
+                10,
+                None,
+                &mut callbacks,
+            )
+            .unwrap();
+        assert_eq!(history.epochs.len(), 2);
+        assert_eq!(predict(&mut model), ndarray::arr1(&[2.5, 0.0]).into_dyn());
+
+        // the best weights are also restored if training runs out of epochs before stopping
+        let mut model = bias_model();
+        let mut callbacks: Vec<Box<dyn Callback>> =
+            vec![Box::new(EarlyStopping::new("loss", Mode::Min, 10, true))];
+        let history = model
+            .fit(
+                &mut diverging_dataset(),
+                DIVERGING_LEARNING_RATE,
+                3,
+                None,
+                &mut callbacks,
+            )
+            .unwrap();
+        assert_eq!(history.epochs.len(), 3);
+        assert_eq!(predict(&mut model), ndarray::arr1(&[2.5, 0.0]).into_dyn());
+
+        let mut callbacks: Vec<Box<dyn Callback>> =
+            vec![Box::new(EarlyStopping::new("val_loss", Mode::Min, 1, true))];
+        assert!(model
+            .fit(&mut diverging_dataset(), 0.1, 1, None, &mut callbacks)
+            .is_err());
+
+        // the best weights are also restored if training fails
+        let mut model = bias_model();
+        let mut callbacks: Vec<Box<dyn Callback>> = vec![
+            Box::new(EarlyStopping::new("loss", Mode::Min, 10, true)),
+            Box::new(FailingCallback { epoch: 2 }),
+        ];
+        assert!(model
+            .fit(
+                &mut diverging_dataset(),
+                DIVERGING_LEARNING_RATE,
+                10,
+                None,
+                &mut callbacks,
+            )
+            .is_err());
+        assert_eq!(predict(&mut model), ndarray::arr1(&[2.5, 0.0]).into_dyn());
+    }
+
+    #[test]
+    fn test_model_checkpoint() {
+        let path = std::env::temp_dir().join("neural_net_test_model_checkpoint");
+        let mut model = bias_model();
+        let mut callbacks: Vec<Box<dyn Callback>> = vec![Box::new(ModelCheckpoint::new(
+            &path,
+            "loss",
+            Mode::Min,
+            true,
+        ))];
+        model
+            .fit(
+                &mut diverging_dataset(),
+                DIVERGING_LEARNING_RATE,
+                3,
+                None,
+                &mut callbacks,
+            )
+            .unwrap();
+        assert_ne!(predict(&mut model), ndarray::arr1(&[2.5, 0.0]).into_dyn());
+        model.load_weights(&path).unwrap();
+        assert_eq!(predict(&mut model), ndarray::arr1(&[2.5, 0.0]).into_dyn());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reduce_lr_on_plateau() {
+        // the sample has no weight, so the loss never improves
+        let mut dataset = TestDataset::new(&[[1.0, 0.0]]);
+        dataset.weights[0] = 0.0;
+        let mut model = bias_model();
+        let mut callbacks: Vec<Box<dyn Callback>> = vec![Box::new(ReduceLROnPlateau::new(
+            "loss",
+            Mode::Min,
+            0.5,
+            1,
+            0.2,
+        ))];
+        model
+            .fit(&mut dataset, 1.0, 3, None, &mut callbacks)
+            .unwrap();
+        assert_eq!(model.learning_rate(), 0.25);
+        model
+            .fit(&mut dataset, 1.0, 4, None, &mut callbacks)
+            .unwrap();
+        assert_eq!(model.learning_rate(), 0.2);
+    }
+
+    #[test]
+    fn test_csv_logger() {
+        let path = std::env::temp_dir().join("neural_net_test_csv_logger.csv");
+        let mut model = bias_model();
+        let mut callbacks: Vec<Box<dyn Callback>> = vec![Box::new(CSVLogger::new(&path))];
+        model
+            .fit(
+                &mut diverging_dataset(),
+                DIVERGING_LEARNING_RATE,
+                2,
+                Some(&mut diverging_dataset()),
+                &mut callbacks,
+            )
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "epoch,loss,val_loss\n0,0.5,1.125\n1,1.125,2.53125\n"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_gradient_norm() {
+        let mut model = bias_model();
+        model.set_gradient_clipping(clipping::GradientClipping {
+            global_norm: Some(0.5),
+            ..Default::default()
+        });
+        model.set_metrics(vec![Box::new(metrics::AUC::new(0))]);
+        let logs = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut callbacks: Vec<Box<dyn Callback>> =
+            vec![Box::new(BatchLogsRecorder { logs: logs.clone() })];
+        model
+            .fit(&mut diverging_dataset(), 1.0, 2, None, &mut callbacks)
+            .unwrap();
+
+        // the norms are reported before clipping, which only affects the first step
+        let norms: Vec<_> = logs.borrow().iter().map(|l| l["gradient_norm"]).collect();
+        assert_eq!(norms, vec![1.0, 0.5]);
+        assert_eq!(predict(&mut model), ndarray::arr1(&[1.0, 0.0]).into_dyn());
+
+        // the metrics are only computed for epochs
+        assert!(logs.borrow().iter().all(|l| l.len() == 2));
+    }
+}
@@ -0,0 +1,138 @@
+// GradientClipping limits the gradients used to update variables during training. Each limit is
+// optional. They're applied in the order of the fields: first the norm of each variable's
+// gradient, then the global norm across all of them, then each element.
+#[derive(Clone, Copy, Default)]
+pub struct GradientClipping {
+    pub norm: Option<f32>,
+    pub global_norm: Option<f32>,
+    pub value: Option<f32>,
+}
+
+impl GradientClipping {
+    // Returns true if any limit is set.
+    pub fn is_enabled(&self) -> bool {
+        self.norm.is_some() || self.global_norm.is_some() || self.value.is_some()
+    }
+
+    pub fn apply(&self, gradients: &mut [ndarray::ArrayD<f32>]) {
+        if let Some(max_norm) = self.norm {
+            for gradient in gradients.iter_mut() {
+                clip_by_norm(gradient, max_norm);
+            }
+        }
+        if let Some(max_norm) = self.global_norm {
+            clip_by_global_norm(gradients, max_norm);
+        }
+        if let Some(max_value) = self.value {
+            for gradient in gradients.iter_mut() {
+                clip_by_value(gradient, max_value);
+            }
+        }
+    }
+}
+
+// Clamps each element to [-max_value, max_value].
+pub fn clip_by_value(gradient: &mut ndarray::ArrayD<f32>, max_value: f32) {
+    gradient.mapv_inplace(|v| v.max(-max_value).min(max_value));
+}
+
+// Scales the gradient down so that its L2 norm is at most max_norm.
+pub fn clip_by_norm(gradient: &mut ndarray::ArrayD<f32>, max_norm: f32) {
+    let norm = gradient.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > max_norm {
+        *gradient *= max_norm / norm;
+    }
+}
+
+// Returns the L2 norm of all of the gradients combined.
+pub fn global_norm<'a, I>(gradients: I) -> f32
+where
+    I: IntoIterator<Item = &'a ndarray::ArrayD<f32>>,
+{
+    gradients
+        .into_iter()
+        .map(|g| g.iter().map(|v| v * v).sum::<f32>())
+        .sum::<f32>()
+        .sqrt()
+}
+
+// Scales all of the gradients down by the same factor so that their global norm is at most
+// max_norm. This preserves their relative directions, unlike clipping each one individually.
+pub fn clip_by_global_norm(gradients: &mut [ndarray::ArrayD<f32>], max_norm: f32) {
+    let norm = global_norm(gradients.iter());
+    if norm > max_norm {
+        for gradient in gradients.iter_mut() {
+            *gradient *= max_norm / norm;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradients() -> Vec<ndarray::ArrayD<f32>> {
+        vec![
+            ndarray::arr1(&[3.0, -4.0]).into_dyn(),
+            ndarray::arr1(&[0.0, 12.0]).into_dyn(),
+        ]
+    }
+
+    #[test]
+    fn test_clip_by_value() {
+        let mut g = gradients();
+        GradientClipping {
+            value: Some(3.5),
+            ..Default::default()
+        }
+        .apply(&mut g);
+        assert_eq!(
+            g,
+            vec![
+                ndarray::arr1(&[3.0, -3.5]).into_dyn(),
+                ndarray::arr1(&[0.0, 3.5]).into_dyn(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clip_by_norm() {
+        let mut g = gradients();
+        GradientClipping {
+            norm: Some(6.0),
+            ..Default::default()
+        }
+        .apply(&mut g);
+        assert_eq!(
+            g,
+            vec![
+                ndarray::arr1(&[3.0, -4.0]).into_dyn(),
+                ndarray::arr1(&[0.0, 6.0]).into_dyn(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clip_by_global_norm() {
+        let mut g = gradients();
+        assert_eq!(global_norm(&g), 13.0);
+        assert!(!GradientClipping::default().is_enabled());
+        GradientClipping {
+            global_norm: Some(6.5),
+            ..Default::default()
+        }
+        .apply(&mut g);
+        assert_eq!(
+            g,
+            vec![
+                ndarray::arr1(&[1.5, -2.0]).into_dyn(),
+                ndarray::arr1(&[0.0, 6.0]).into_dyn(),
+            ]
+        );
+
+        // gradients within the limit are unchanged
+        let mut g = gradients();
+        clip_by_global_norm(&mut g, 20.0);
+        assert_eq!(g, gradients());
+    }
+}
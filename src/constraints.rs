@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+// A constraint is applied to a variable's value after each optimizer step.
+pub type Constraint = Rc<dyn Fn(ndarray::ArrayD<f32>) -> ndarray::ArrayD<f32>>;
+
+// This is the fuzz factor Keras uses to avoid dividing by zero.
+const EPSILON: f32 = 1e-7;
+
+// Computes the euclidean norms over the given axes, keeping them as axes of length 1 so that the
+// result broadcasts against the input.
+fn norms(w: &ndarray::ArrayD<f32>, axes: &[usize]) -> ndarray::ArrayD<f32> {
+    let mut result = w.mapv(|v| v * v);
+    for &axis in axes {
+        result = result
+            .sum_axis(ndarray::Axis(axis))
+            .insert_axis(ndarray::Axis(axis));
+    }
+    result.mapv(f32::sqrt)
+}
+
+// Limits the norm over the given axes to max_value. For Dense kernels, use axis 1 to constrain the
+// weights of each unit.
+pub fn max_norm(max_value: f32, axes: Vec<usize>) -> Constraint {
+    Rc::new(move |w| {
+        let norms = norms(&w, &axes);
+        let desired = norms.mapv(|n| n.min(max_value));
+        w * &(desired / &(norms + EPSILON))
+    })
+}
+
+// Scales the values so that the norm over the given axes is 1.
+pub fn unit_norm(axes: Vec<usize>) -> Constraint {
+    Rc::new(move |w| {
+        let norms = norms(&w, &axes);
+        w / &(norms + EPSILON)
+    })
+}
+
+pub fn non_neg() -> Constraint {
+    Rc::new(|w| w.mapv(|v| v.max(0.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let w = ndarray::arr2(&[[3.0, 4.0], [0.3, -0.4]]).into_dyn();
+        assert!(max_norm(1.0, vec![1])(w.clone())
+            .all_close(&ndarray::arr2(&[[0.6, 0.8], [0.3, -0.4]]).into_dyn(), 1e-6));
+        assert!(unit_norm(vec![1])(w.clone())
+            .all_close(&ndarray::arr2(&[[0.6, 0.8], [0.6, -0.8]]).into_dyn(), 1e-6));
+        assert!(unit_norm(vec![0, 1])(w.clone()).all_close(&(w.clone() / 25.25f32.sqrt()), 1e-6));
+        assert_eq!(
+            non_neg()(w),
+            ndarray::arr2(&[[3.0, 4.0], [0.3, 0.0]]).into_dyn()
+        );
+    }
+}
@@ -1,20 +1,217 @@
+use std::cell::RefCell;
+
 use rand::distributions::{Distribution, Uniform};
 use rand::SeedableRng;
 
 use ndarray::Dimension;
 
+// The random initializers draw from a thread-local generator. Each layer restarts it with a stream
+// derived from the model's seed and the layer's namespace, so initialization is reproducible, but
+// layers with the same shape don't start out with the same weights.
+struct Generator {
+    seed: u64,
+    rng: rand::rngs::StdRng,
+}
+
+thread_local! {
+    static GENERATOR: RefCell<Generator> = RefCell::new(Generator {
+        seed: 0,
+        rng: rand::rngs::StdRng::seed_from_u64(0),
+    });
+}
+
+// Sets the seed that namespace streams are derived from and restarts the generator with it.
+pub fn set_seed(seed: u64) {
+    GENERATOR.with(|g| {
+        let mut g = g.borrow_mut();
+        g.seed = seed;
+        g.rng = rand::rngs::StdRng::seed_from_u64(seed);
+    });
+}
+
+// Restarts the generator with the stream for the given namespace. This is done for each layer by
+// LayerVariablesBuilder.
+pub fn use_namespace(namespace: &str) {
+    // FNV-1a is used instead of the standard library's hasher, which isn't guaranteed to be stable
+    // across releases.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in namespace.bytes() {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    GENERATOR.with(|g| {
+        let mut g = g.borrow_mut();
+        g.rng = rand::rngs::StdRng::seed_from_u64(g.seed ^ hash);
+    });
+}
+
+fn sample<F: FnMut(&mut rand::rngs::StdRng) -> f32>(
+    shape: &ndarray::IxDyn,
+    mut f: F,
+) -> ndarray::ArrayD<f32> {
+    GENERATOR.with(|g| {
+        let rng = &mut g.borrow_mut().rng;
+        ndarray::Array::from_shape_fn(shape.clone(), |_| f(rng))
+    })
+}
+
+// Uses the Box-Muller transform since the normal distributions have moved out of rand.
+fn standard_normal(rng: &mut rand::rngs::StdRng) -> f64 {
+    let u1: f64 = 1.0 - Uniform::new(0.0, 1.0).sample(rng);
+    let u2: f64 = Uniform::new(0.0, 1.0).sample(rng);
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+// Returns the fan in and fan out for a kernel. 2-dimensional kernels are expected to be in the
+// (outputs, inputs) layout used by Dense. Higher dimensional kernels are expected to be in the
+// (spatial..., inputs, outputs) layout used by Conv2D.
+fn fans(shape: &ndarray::IxDyn) -> (usize, usize) {
+    let shape = shape.slice();
+    match shape.len() {
+        0 => (1, 1),
+        1 => (shape[0], shape[0]),
+        2 => (shape[1], shape[0]),
+        n => {
+            let receptive_field: usize = shape[..n - 2].iter().product();
+            (
+                receptive_field * shape[n - 2],
+                receptive_field * shape[n - 1],
+            )
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum FanMode {
+    FanIn,
+    FanOut,
+    FanAvg,
+}
+
+#[derive(Clone, Copy)]
+pub enum VarianceScalingDistribution {
+    TruncatedNormal,
+    UntruncatedNormal,
+    Uniform,
+}
+
+// Samples with a variance of scale / n, where n is determined by the mode.
+pub fn variance_scaling(
+    scale: f32,
+    mode: FanMode,
+    distribution: VarianceScalingDistribution,
+) -> impl Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    move |shape| {
+        let (fan_in, fan_out) = fans(shape);
+        let n = match mode {
+            FanMode::FanIn => fan_in as f32,
+            FanMode::FanOut => fan_out as f32,
+            FanMode::FanAvg => (fan_in + fan_out) as f32 / 2.0,
+        };
+        let scale = scale / n.max(1.0);
+        match distribution {
+            VarianceScalingDistribution::TruncatedNormal => {
+                // This is the standard deviation of a standard normal truncated to (-2, 2).
+                truncated_normal(0.0, scale.sqrt() / 0.879_625_7)(shape)
+            }
+            VarianceScalingDistribution::UntruncatedNormal => {
+                random_normal(0.0, scale.sqrt())(shape)
+            }
+            VarianceScalingDistribution::Uniform => {
+                let limit = (3.0 * scale).sqrt();
+                random_uniform(-limit, limit)(shape)
+            }
+        }
+    }
+}
+
 pub fn glorot_uniform(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
-    let shape_arr = shape.as_array_view();
-    let fan_in_plus_out = match shape_arr.len() {
-        2 => shape_arr.sum(),
-        3 => shape_arr[0] * (shape_arr[1] + shape_arr[2]),
-        4 => shape_arr[0] * shape_arr[1] * (shape_arr[2] + shape_arr[3]),
-        _ => panic!("unsupported shape dimensionality for glorot uniform"),
-    };
-    let limit = (6.0 / fan_in_plus_out as f32).sqrt();
-    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
-    let dist = Uniform::new_inclusive(-limit, limit);
-    ndarray::Array::from_shape_fn(shape.clone(), |_| dist.sample(&mut rng))
+    variance_scaling(1.0, FanMode::FanAvg, VarianceScalingDistribution::Uniform)(shape)
+}
+
+pub fn glorot_normal(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    variance_scaling(
+        1.0,
+        FanMode::FanAvg,
+        VarianceScalingDistribution::TruncatedNormal,
+    )(shape)
+}
+
+pub fn he_uniform(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    variance_scaling(2.0, FanMode::FanIn, VarianceScalingDistribution::Uniform)(shape)
+}
+
+pub fn he_normal(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    variance_scaling(
+        2.0,
+        FanMode::FanIn,
+        VarianceScalingDistribution::TruncatedNormal,
+    )(shape)
+}
+
+pub fn lecun_uniform(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    variance_scaling(1.0, FanMode::FanIn, VarianceScalingDistribution::Uniform)(shape)
+}
+
+pub fn lecun_normal(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    variance_scaling(
+        1.0,
+        FanMode::FanIn,
+        VarianceScalingDistribution::TruncatedNormal,
+    )(shape)
+}
+
+pub fn random_uniform(min: f32, max: f32) -> impl Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    move |shape| {
+        let dist = Uniform::new_inclusive(min, max);
+        sample(shape, |rng| dist.sample(rng))
+    }
+}
+
+pub fn random_normal(mean: f32, stddev: f32) -> impl Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    move |shape| sample(shape, |rng| mean + stddev * standard_normal(rng) as f32)
+}
+
+// Samples from a normal distribution, redrawing any values more than two standard deviations from
+// the mean.
+pub fn truncated_normal(
+    mean: f32,
+    stddev: f32,
+) -> impl Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    move |shape| {
+        sample(shape, |rng| loop {
+            let v = standard_normal(rng);
+            if v.abs() <= 2.0 {
+                return mean + stddev * v as f32;
+            }
+        })
+    }
+}
+
+// Generates a matrix with orthonormal rows or columns, scaled by gain. All but the last axis are
+// flattened to form the rows of the matrix.
+pub fn orthogonal(gain: f32) -> impl Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    move |shape| {
+        let cols = shape.slice().last().cloned().unwrap_or(1);
+        let rows = shape.size() / cols.max(1);
+        let (n, k) = (rows.max(cols), rows.min(cols));
+        let a = random_normal(0.0, 1.0)(&ndarray::IxDyn(&[n, k])).mapv(f64::from);
+        let mut q = ndarray::Array2::<f64>::zeros((n, k));
+        // modified gram-schmidt
+        for j in 0..k {
+            let mut v = a.slice(s![.., j]).to_owned();
+            for i in 0..j {
+                let qi = q.column(i);
+                let r = qi.dot(&v);
+                v.scaled_add(-r, &qi);
+            }
+            let norm = v.dot(&v).sqrt();
+            q.column_mut(j).assign(&(v / norm));
+        }
+        let q = if rows < cols { q.reversed_axes() } else { q };
+        ndarray::Array::from_shape_vec(shape.clone(), q.iter().map(|&v| gain * v as f32).collect())
+            .unwrap()
+    }
 }
 
 pub fn zeros(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
@@ -25,6 +222,10 @@ pub fn ones(shape: &ndarray::IxDyn) -> ndarray::ArrayD<f32> {
     ndarray::Array::ones(shape.clone())
 }
 
+pub fn constant(value: f32) -> impl Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> {
+    move |shape| ndarray::Array::from_elem(shape.clone(), value)
+}
+
 pub fn copy(v: ndarray::ArrayD<f32>) -> impl Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> {
     move |shape| {
         if *shape != v.dim() {
@@ -37,3 +238,74 @@ pub fn copy(v: ndarray::ArrayD<f32>) -> impl Fn(&ndarray::IxDyn) -> ndarray::Arr
         v.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variance(a: &ndarray::ArrayD<f32>) -> f32 {
+        let mean = a.mean_axis(ndarray::Axis(0)).mean_axis(ndarray::Axis(0));
+        let mean = *mean.first().unwrap();
+        a.mapv(|v| (v - mean) * (v - mean)).sum() / a.len() as f32
+    }
+
+    #[test]
+    fn test_variance_scaling() {
+        let shape = ndarray::IxDyn(&[200, 100]);
+        for &(initializer, expected) in &[
+            (
+                he_normal as fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32>,
+                0.02,
+            ),
+            (he_uniform, 0.02),
+            (lecun_normal, 0.01),
+            (lecun_uniform, 0.01),
+            (glorot_normal, 1.0 / 150.0),
+            (glorot_uniform, 1.0 / 150.0),
+        ] {
+            let v = variance(&initializer(&shape));
+            assert!(
+                (v - expected).abs() < expected * 0.05,
+                "{} != {}",
+                v,
+                expected
+            );
+        }
+
+        let a = truncated_normal(1.0, 0.5)(&shape);
+        assert!(a.iter().all(|v| (0.0..=2.0).contains(v)));
+    }
+
+    #[test]
+    fn test_orthogonal() {
+        for &shape in &[[3, 5], [5, 3]] {
+            let a = orthogonal(2.0)(&ndarray::IxDyn(&shape))
+                .into_dimensionality::<ndarray::Ix2>()
+                .unwrap();
+            let product = if shape[0] < shape[1] {
+                a.dot(&a.t())
+            } else {
+                a.t().dot(&a)
+            };
+            assert!(product.all_close(&(ndarray::Array2::<f32>::eye(3) * 4.0), 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_seeding() {
+        let shape = ndarray::IxDyn(&[4, 4]);
+        set_seed(1);
+        use_namespace("l0");
+        let a = glorot_uniform(&shape);
+        use_namespace("l1");
+        let b = glorot_uniform(&shape);
+        assert_ne!(a, b);
+
+        use_namespace("l0");
+        assert_eq!(glorot_uniform(&shape), a);
+
+        set_seed(2);
+        use_namespace("l0");
+        assert_ne!(glorot_uniform(&shape), a);
+    }
+}
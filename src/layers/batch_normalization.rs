@@ -67,6 +67,7 @@ where
                     )
             },
             variables: lv_builder.variables,
+            regularization_losses: lv_builder.regularization_losses,
         })
     }
 }
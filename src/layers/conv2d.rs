@@ -1,4 +1,4 @@
-use super::super::{algebra, Layer, LayerInstance};
+use super::super::{algebra, constraints, regularizers, Layer, LayerInstance};
 use super::LayerVariablesBuilder;
 
 use ndarray::Dimension;
@@ -14,7 +14,13 @@ where
 {
     pub activation: Activation,
     pub bias_initializer: BiasInitializer,
+    // The kernel initializer, regularizer, and constraint all receive the kernel, whose shape is
+    // (height, width, in_channels, filters).
     pub kernel_initializer: KernelInitializer,
+    pub kernel_regularizer: Option<regularizers::Regularizer>,
+    // The bias regularizer receives the bias, whose shape is (filters).
+    pub bias_regularizer: Option<regularizers::Regularizer>,
+    pub kernel_constraint: Option<constraints::Constraint>,
     pub filters: usize,
     pub kernel_size: ndarray::Ix2,
     pub padding: Padding,
@@ -37,13 +43,15 @@ where
         let mut lv_builder = LayerVariablesBuilder::new(namespace);
         let in_channels = input_shape.as_array_view()[2];
         let biases = match self.use_bias {
-            true => Some(lv_builder.append(
+            true => Some(lv_builder.append_with(
                 "b",
                 (self.bias_initializer)(&ndarray::Ix1(self.filters).into_dyn()),
+                self.bias_regularizer,
+                None,
             )),
             false => None,
         };
-        let kernel = lv_builder.append(
+        let kernel = lv_builder.append_with(
             "w",
             (self.kernel_initializer)(&ndarray::IxDyn(&[
                 self.kernel_size[0],
@@ -51,6 +59,8 @@ where
                 in_channels,
                 self.filters,
             ])),
+            self.kernel_regularizer,
+            self.kernel_constraint,
         );
         let activation = self.activation;
         let stride = self.stride;
@@ -64,6 +74,7 @@ where
                 (activation)(result)
             },
             variables: lv_builder.variables,
+            regularization_losses: lv_builder.regularization_losses,
         })
     }
 }
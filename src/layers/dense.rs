@@ -1,4 +1,4 @@
-use super::super::{algebra, Layer, LayerInstance};
+use super::super::{algebra, constraints, regularizers, Layer, LayerInstance};
 use super::LayerVariablesBuilder;
 
 use ndarray::Dimension;
@@ -10,7 +10,13 @@ where
     KernelInitializer: Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32>,
 {
     pub activation: Activation,
+    // The kernel initializer, regularizer, and constraint all receive the kernel, whose shape is
+    // (output_size, input_size).
     pub kernel_initializer: KernelInitializer,
+    pub kernel_regularizer: Option<regularizers::Regularizer>,
+    // The bias regularizer receives the bias, whose shape is (output_size).
+    pub bias_regularizer: Option<regularizers::Regularizer>,
+    pub kernel_constraint: Option<constraints::Constraint>,
     pub output_size: usize,
 }
 
@@ -26,18 +32,26 @@ where
     ) -> Box<dyn LayerInstance> {
         let mut lv_builder = LayerVariablesBuilder::new(namespace);
         let activation = self.activation;
-        let biases = lv_builder.append("b", ndarray::Array::zeros(self.output_size));
-        let weights = lv_builder.append(
+        let biases = lv_builder.append_with(
+            "b",
+            ndarray::Array::zeros(self.output_size),
+            self.bias_regularizer,
+            None,
+        );
+        let weights = lv_builder.append_with(
             "w",
             (self.kernel_initializer)(
                 &ndarray::Ix2(self.output_size, input_shape.size()).into_dyn(),
             ),
+            self.kernel_regularizer,
+            self.kernel_constraint,
         );
         Box::new(super::Instance {
             expression: move |input| {
                 (activation)(algebra::matvecmul(weights.clone(), input) + biases.clone())
             },
             variables: lv_builder.variables,
+            regularization_losses: lv_builder.regularization_losses,
         })
     }
 }
@@ -56,6 +70,9 @@ mod tests {
             Box::new(Dense {
                 activation: |x| x + 1.0,
                 kernel_initializer: initializers::zeros,
+                kernel_regularizer: None,
+                bias_regularizer: None,
+                kernel_constraint: None,
                 output_size: 4,
             })
             .init("l", &a.dim())
@@ -73,6 +90,9 @@ mod tests {
         let l = Box::new(Dense {
             activation: activations::softmax,
             kernel_initializer: initializers::zeros,
+            kernel_regularizer: None,
+            bias_regularizer: None,
+            kernel_constraint: None,
             output_size: 3,
         })
         .init("l", &input.shape())
@@ -14,6 +14,7 @@ impl Layer for Flatten {
         Box::new(super::Instance {
             expression: move |input| input.reshape(ndarray::Ix1(output_size)),
             variables: vec![],
+            regularization_losses: vec![],
         })
     }
 }
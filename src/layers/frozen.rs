@@ -0,0 +1,78 @@
+use super::super::{algebra, Layer, LayerInstance, LayerVariable};
+
+// Frozen wraps a layer, such as a pretrained Sequential, so that none of its variables are trained.
+pub struct Frozen {
+    pub layer: Box<dyn Layer>,
+}
+
+struct FrozenInstance {
+    instance: Box<dyn LayerInstance>,
+    variables: Vec<LayerVariable>,
+}
+
+impl LayerInstance for FrozenInstance {
+    fn expression(&self, input: algebra::Expr) -> algebra::Expr {
+        self.instance.expression(input)
+    }
+
+    fn variables(&self) -> &[LayerVariable] {
+        self.variables.as_slice()
+    }
+
+    fn regularization_losses(&self) -> &[algebra::Expr] {
+        self.instance.regularization_losses()
+    }
+
+    fn output_shape(&self, input_shape: &ndarray::IxDyn) -> ndarray::IxDyn {
+        self.instance.output_shape(input_shape)
+    }
+}
+
+impl Layer for Frozen {
+    fn init(
+        self: Box<Self>,
+        namespace: &str,
+        input_shape: &ndarray::IxDyn,
+    ) -> Box<dyn LayerInstance> {
+        let instance = self.layer.init(namespace, input_shape);
+        let variables = instance
+            .variables()
+            .iter()
+            .map(|v| LayerVariable {
+                trainable: false,
+                ..v.clone()
+            })
+            .collect();
+        Box::new(FrozenInstance {
+            instance,
+            variables,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{initializers, layers};
+    use super::*;
+
+    #[test]
+    fn test() {
+        let a = ndarray::arr1(&[1.0, 2.0]).into_dyn();
+        let instance = Box::new(Frozen {
+            layer: Box::new(layers::Sequential {
+                layers: vec![Box::new(layers::Dense {
+                    activation: |x| x,
+                    kernel_initializer: initializers::ones,
+                    kernel_regularizer: None,
+                    bias_regularizer: None,
+                    kernel_constraint: None,
+                    output_size: 1,
+                })],
+            }),
+        })
+        .init("l", &a.dim());
+        assert_eq!(instance.variables().len(), 2);
+        assert!(instance.variables().iter().all(|v| !v.trainable));
+        assert_eq!(instance.eval(a.view()), ndarray::arr1(&[3.0]).into_dyn());
+    }
+}
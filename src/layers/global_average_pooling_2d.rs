@@ -17,6 +17,7 @@ impl Layer for GlobalAveragePooling2D {
                     / (input_shape[0] * input_shape[1]) as f32
             },
             variables: vec![],
+            regularization_losses: vec![],
         })
     }
 }
@@ -20,6 +20,7 @@ where
         Box::new(super::Instance {
             expression: self.f,
             variables: vec![],
+            regularization_losses: vec![],
         })
     }
 }
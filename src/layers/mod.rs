@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use super::{algebra, LayerInstance, LayerVariable};
+use super::{algebra, constraints, initializers, regularizers, LayerInstance, LayerVariable};
 
 pub mod batch_normalization;
 pub use batch_normalization::*;
@@ -10,10 +10,18 @@ pub mod dense;
 pub use dense::*;
 pub mod flatten;
 pub use flatten::*;
+pub mod frozen;
+pub use frozen::*;
 pub mod global_average_pooling_2d;
 pub use global_average_pooling_2d::*;
 pub mod lambda;
 pub use lambda::*;
+pub mod parametric_activation;
+pub use parametric_activation::*;
+pub mod permute;
+pub use permute::*;
+pub mod prelu;
+pub use prelu::*;
 pub mod residual;
 pub use residual::*;
 pub mod sequential;
@@ -22,17 +30,36 @@ pub use sequential::*;
 struct LayerVariablesBuilder {
     namespace: String,
     variables: Vec<super::LayerVariable>,
+    regularization_losses: Vec<algebra::Expr>,
 }
 
 impl LayerVariablesBuilder {
+    // This also gives the layer its own stream of random numbers for initialization.
     fn new<S: Into<String>>(namespace: S) -> Self {
+        let namespace = namespace.into();
+        initializers::use_namespace(&namespace);
         LayerVariablesBuilder {
-            namespace: namespace.into(),
+            namespace,
             variables: Vec::new(),
+            regularization_losses: Vec::new(),
         }
     }
 
     fn append<S1, D>(&mut self, name: &str, init: ndarray::ArrayBase<S1, D>) -> algebra::Expr
+    where
+        S1: ndarray::Data<Elem = f32>,
+        D: ndarray::Dimension,
+    {
+        self.append_with(name, init, None, None)
+    }
+
+    fn append_with<S1, D>(
+        &mut self,
+        name: &str,
+        init: ndarray::ArrayBase<S1, D>,
+        regularizer: Option<regularizers::Regularizer>,
+        constraint: Option<constraints::Constraint>,
+    ) -> algebra::Expr
     where
         S1: ndarray::Data<Elem = f32>,
         D: ndarray::Dimension,
@@ -40,9 +67,15 @@ impl LayerVariablesBuilder {
         let v = super::LayerVariable {
             name: format!("{}.{}", self.namespace, name),
             value: Rc::new(algebra::VariableValue::new(init)),
+            constraint,
+            trainable: true,
         };
         self.variables.push(v.clone());
-        algebra::v(v.name, v.value)
+        let expr = algebra::v(v.name, v.value);
+        if let Some(regularizer) = regularizer {
+            self.regularization_losses.push(regularizer(expr.clone()));
+        }
+        expr
     }
 }
 
@@ -52,6 +85,7 @@ where
 {
     expression: F,
     variables: Vec<LayerVariable>,
+    regularization_losses: Vec<algebra::Expr>,
 }
 
 impl<F> LayerInstance for Instance<F>
@@ -65,4 +99,8 @@ where
     fn variables(&self) -> &[LayerVariable] {
         self.variables.as_slice()
     }
+
+    fn regularization_losses(&self) -> &[algebra::Expr] {
+        self.regularization_losses.as_slice()
+    }
 }
@@ -0,0 +1,98 @@
+use super::super::{algebra, Layer, LayerInstance};
+use super::LayerVariablesBuilder;
+
+use ndarray::Dimension;
+
+pub type ParameterInitializer = Box<dyn Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32>>;
+
+pub struct ActivationParameter {
+    pub name: String,
+    pub initializer: ParameterInitializer,
+}
+
+// ParametricActivation applies an activation function with learnable parameters. Each parameter
+// has the shape of the input, except that the shared axes have length 1 so that a single value is
+// learned across them. The parameters are broadcast to the input's shape before they're given to
+// the activation function, in the same order they were declared.
+pub struct ParametricActivation<Activation>
+where
+    Activation: Fn(algebra::Expr, &[algebra::Expr]) -> algebra::Expr + 'static,
+{
+    pub activation: Activation,
+    pub parameters: Vec<ActivationParameter>,
+    pub shared_axes: Vec<usize>,
+}
+
+impl<Activation> Layer for ParametricActivation<Activation>
+where
+    Activation: Fn(algebra::Expr, &[algebra::Expr]) -> algebra::Expr + 'static,
+{
+    fn init(
+        self: Box<Self>,
+        namespace: &str,
+        input_shape: &ndarray::IxDyn,
+    ) -> Box<dyn LayerInstance> {
+        let mut lv_builder = LayerVariablesBuilder::new(namespace);
+        let mut parameter_shape = input_shape.clone();
+        for &axis in self.shared_axes.iter() {
+            if axis >= input_shape.ndim() {
+                panic!(
+                    "shared axis {} is out of bounds for input shape {:?}",
+                    axis, input_shape
+                );
+            }
+            parameter_shape[axis] = 1;
+        }
+        let input_shape = input_shape.clone();
+        let parameters: Vec<_> = self
+            .parameters
+            .iter()
+            .map(|p| {
+                let v = lv_builder.append(&p.name, (p.initializer)(&parameter_shape));
+                if parameter_shape == input_shape {
+                    v
+                } else {
+                    algebra::broadcast_to(v, input_shape.clone())
+                }
+            })
+            .collect();
+        let activation = self.activation;
+        Box::new(super::Instance {
+            expression: move |input| (activation)(input, &parameters),
+            variables: lv_builder.variables,
+            regularization_losses: lv_builder.regularization_losses,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::initializers;
+    use super::*;
+
+    #[test]
+    fn test() {
+        let a = ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]).into_dyn();
+        let instance = Box::new(ParametricActivation {
+            activation: |x, p: &[algebra::Expr]| x * p[0].clone() + p[1].clone(),
+            parameters: vec![
+                ActivationParameter {
+                    name: "scale".to_string(),
+                    initializer: Box::new(initializers::constant(2.0)),
+                },
+                ActivationParameter {
+                    name: "shift".to_string(),
+                    initializer: Box::new(initializers::ones),
+                },
+            ],
+            shared_axes: vec![0],
+        })
+        .init("l", &a.dim());
+        assert_eq!(instance.variables()[0].name, "l.scale");
+        assert_eq!(instance.variables()[0].value.get().shape(), &[1, 2]);
+        assert_eq!(
+            instance.eval(a.view()),
+            ndarray::arr2(&[[3.0, 5.0], [7.0, 9.0]]).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use super::super::{algebra, Layer, LayerInstance};
+
+// Permute reorders the axes of its input. Axis i of the output is axis axes[i] of the input.
+pub struct Permute {
+    pub axes: Vec<usize>,
+}
+
+impl Layer for Permute {
+    fn init(
+        self: Box<Self>,
+        _namespace: &str,
+        _input_shape: &ndarray::IxDyn,
+    ) -> Box<dyn LayerInstance> {
+        let axes = self.axes;
+        Box::new(super::Instance {
+            expression: move |input| algebra::transpose_axes(input, axes.clone()),
+            variables: vec![],
+            regularization_losses: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let nhwc = ndarray::Array::range(0.0, 12.0, 1.0)
+            .into_shape((1, 2, 2, 3))
+            .unwrap()
+            .into_dyn();
+        let nchw = Box::new(Permute {
+            axes: vec![0, 3, 1, 2],
+        })
+        .init("l", &nhwc.dim())
+        .eval(nhwc.view());
+        assert_eq!(nchw.shape(), &[1, 3, 2, 2]);
+        assert_eq!(
+            nchw,
+            nhwc.view().permuted_axes(ndarray::IxDyn(&[0, 3, 1, 2]))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use super::super::{algebra, Layer, LayerInstance};
+use super::{ActivationParameter, ParametricActivation};
+
+// PReLU is a leaky relu whose slope for negative inputs is learned. By default a slope is learned
+// for every element of the input. For convolutional layers, sharing the slope across the spatial
+// axes learns one slope per channel.
+pub struct PReLU<AlphaInitializer>
+where
+    AlphaInitializer: Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> + 'static,
+{
+    pub alpha_initializer: AlphaInitializer,
+    pub shared_axes: Vec<usize>,
+}
+
+impl<AlphaInitializer> Layer for PReLU<AlphaInitializer>
+where
+    AlphaInitializer: Fn(&ndarray::IxDyn) -> ndarray::ArrayD<f32> + 'static,
+{
+    fn init(
+        self: Box<Self>,
+        namespace: &str,
+        input_shape: &ndarray::IxDyn,
+    ) -> Box<dyn LayerInstance> {
+        Box::new(ParametricActivation {
+            activation: |input: algebra::Expr, parameters: &[algebra::Expr]| {
+                algebra::relu(input.clone()) - parameters[0].clone() * algebra::relu(-1.0 * input)
+            },
+            parameters: vec![ActivationParameter {
+                name: "alpha".to_string(),
+                initializer: Box::new(self.alpha_initializer),
+            }],
+            shared_axes: self.shared_axes,
+        })
+        .init(namespace, input_shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::initializers;
+    use super::*;
+
+    #[test]
+    fn test() {
+        let a = ndarray::arr3(&[[[-1.0, 2.0], [-3.0, -4.0]]]).into_dyn();
+        let instance = Box::new(PReLU {
+            alpha_initializer: initializers::constant(0.25),
+            shared_axes: vec![0, 1],
+        })
+        .init("l", &a.dim());
+        assert_eq!(instance.variables()[0].name, "l.alpha");
+        assert_eq!(instance.variables()[0].value.get().shape(), &[1, 1, 2]);
+        assert_eq!(
+            instance.eval(a.view()),
+            ndarray::arr3(&[[[-0.25, 2.0], [-0.75, -1.0]]]).into_dyn()
+        );
+
+        let output = instance.expression(algebra::expr(a));
+        assert_eq!(
+            output.gradient("l.alpha").eval(),
+            ndarray::arr3(&[[[-4.0, -4.0]]]).into_dyn()
+        );
+    }
+}
@@ -12,9 +12,11 @@ impl Layer for Residual {
     ) -> Box<dyn LayerInstance> {
         let body = self.body.init(namespace, input_shape);
         let variables = body.variables().to_vec();
+        let regularization_losses = body.regularization_losses().to_vec();
         Box::new(super::Instance {
             expression: move |input| body.expression(input.clone()) + input,
             variables: variables,
+            regularization_losses,
         })
     }
 }
@@ -13,12 +13,14 @@ impl Layer for Sequential {
         let mut input_shape = input_shape.clone();
         let mut layers = Vec::new();
         let mut variables = Vec::new();
+        let mut regularization_losses = Vec::new();
         for (i, layer) in self.layers.drain(..).enumerate() {
             let instance = layer.init(format!("{}/{}", namespace, i).as_str(), &input_shape);
             input_shape = instance.output_shape(&input_shape);
             for v in instance.variables() {
                 variables.push(v.clone());
             }
+            regularization_losses.extend_from_slice(instance.regularization_losses());
             layers.push(instance);
         }
         Box::new(super::Instance {
@@ -30,6 +32,7 @@ impl Layer for Sequential {
                 result
             },
             variables: variables,
+            regularization_losses,
         })
     }
 }
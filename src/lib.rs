@@ -15,6 +15,9 @@ use std::rc::Rc;
 pub struct LayerVariable {
     pub name: String,
     pub value: Rc<algebra::VariableValue>,
+    pub constraint: Option<constraints::Constraint>,
+    // Variables that aren't trainable aren't updated by training, e.g. because they're frozen.
+    pub trainable: bool,
 }
 
 pub trait Layer {
@@ -23,6 +26,13 @@ pub trait Layer {
         namespace: &str,
         input_shape: &ndarray::IxDyn,
     ) -> Box<dyn LayerInstance>;
+
+    // Returns the name of the layer's type for display, e.g. "Dense".
+    fn type_name(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap();
+        name.rsplit("::").next().unwrap().to_string()
+    }
 }
 
 pub trait LayerInstance {
@@ -36,6 +46,11 @@ pub trait LayerInstance {
         &[]
     }
 
+    // These penalties are added to the loss during training.
+    fn regularization_losses(&self) -> &[algebra::Expr] {
+        &[]
+    }
+
     fn output_shape(&self, input_shape: &ndarray::IxDyn) -> ndarray::IxDyn {
         self.expression(algebra::expr(ndarray::Array::zeros(input_shape.clone())))
             .shape()
@@ -48,14 +63,27 @@ pub trait Dataset {
     fn input(&mut self, i: usize) -> Result<ndarray::ArrayViewD<f32>, Box<dyn Error>>;
 
     fn target(&mut self, i: usize) -> Result<ndarray::ArrayViewD<f32>, Box<dyn Error>>;
+
+    // The loss for each sample is multiplied by its weight during training.
+    fn sample_weight(&mut self, _i: usize) -> Result<f32, Box<dyn Error>> {
+        Ok(1.0)
+    }
 }
 
 pub mod activations;
 pub mod algebra;
+pub mod callbacks;
+pub mod clipping;
+pub mod constraints;
 pub mod datasets;
 pub mod graph;
 pub mod initializers;
 pub mod layers;
 pub mod losses;
+pub mod metrics;
 pub mod models;
+pub mod regularizers;
+pub mod schedules;
+#[cfg(test)]
+mod test_util;
 pub mod util;
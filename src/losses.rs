@@ -1,5 +1,404 @@
 use super::algebra;
 
+use ndarray::Dimension;
+
+// This is the fuzz factor used by Keras to keep logarithms finite.
+const EPSILON: f32 = 1e-7;
+
+fn mean(e: algebra::Expr) -> algebra::Expr {
+    let n = e.shape().size() as f32;
+    e.sum() / n
+}
+
+#[derive(Clone, Copy)]
+pub enum Reduction {
+    // Keeps the loss for each sample.
+    None,
+    Sum,
+    // Divides the sum by the number of samples.
+    Mean,
+}
+
+// Combines the losses of individual samples.
+pub fn reduce(losses: &[f32], reduction: Reduction) -> ndarray::ArrayD<f32> {
+    match reduction {
+        Reduction::None => ndarray::arr1(losses).into_dyn(),
+        Reduction::Sum => ndarray::arr0(losses.iter().sum()).into_dyn(),
+        Reduction::Mean => {
+            ndarray::arr0(losses.iter().sum::<f32>() / losses.len().max(1) as f32).into_dyn()
+        }
+    }
+}
+
+// Wraps a categorical loss so that it sees labels that are smoothed towards the uniform
+// distribution: truth * (1 - smoothing) + smoothing / classes.
+pub fn label_smoothing<L>(
+    smoothing: f32,
+    loss: L,
+) -> impl Fn(algebra::Expr, algebra::Expr) -> algebra::Expr
+where
+    L: Fn(algebra::Expr, algebra::Expr) -> algebra::Expr,
+{
+    move |prediction, truth| {
+        let classes = truth.shape().size() as f32;
+        loss(prediction, truth * (1.0 - smoothing) + smoothing / classes)
+    }
+}
+
+// Wraps a binary loss so that it sees labels that are smoothed towards 0.5:
+// truth * (1 - smoothing) + smoothing / 2.
+pub fn binary_label_smoothing<L>(
+    smoothing: f32,
+    loss: L,
+) -> impl Fn(algebra::Expr, algebra::Expr) -> algebra::Expr
+where
+    L: Fn(algebra::Expr, algebra::Expr) -> algebra::Expr,
+{
+    move |prediction, truth| loss(prediction, truth * (1.0 - smoothing) + 0.5 * smoothing)
+}
+
 pub fn categorical_cross_entropy(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
     algebra::expr(0.0) - (truth * prediction.ln()).sum()
 }
+
+// The truth is the index of the correct class.
+pub fn sparse_categorical_cross_entropy(
+    prediction: algebra::Expr,
+    truth: algebra::Expr,
+) -> algebra::Expr {
+    -1.0 * algebra::gather(prediction, truth, 0).ln()
+}
+
+pub fn mean_squared_error(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    mean((prediction - truth).square())
+}
+
+pub fn mean_absolute_error(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    mean((prediction - truth).abs())
+}
+
+// Huber loss is quadratic for errors up to delta and linear beyond that.
+pub fn huber(delta: f32) -> impl Fn(algebra::Expr, algebra::Expr) -> algebra::Expr {
+    move |prediction, truth| {
+        let error = (prediction - truth).abs();
+        let quadratic = error.clip(0.0, delta);
+        let linear = error - quadratic.clone();
+        mean(0.5 * quadratic.square() + delta * linear)
+    }
+}
+
+// Smooth L1 loss is Huber loss divided by beta, as defined by PyTorch.
+pub fn smooth_l1(beta: f32) -> impl Fn(algebra::Expr, algebra::Expr) -> algebra::Expr {
+    let huber = huber(beta);
+    move |prediction, truth| huber(prediction, truth) / beta
+}
+
+pub fn log_cosh(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    // ln(cosh(x)) = x + softplus(-2x) - ln(2), which can't overflow
+    let x = prediction - truth;
+    mean(x.clone() + (-2.0 * x).softplus() - std::f32::consts::LN_2)
+}
+
+// The prediction is a probability. It's clipped to keep the loss finite.
+pub fn binary_cross_entropy(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    let prediction = prediction.clip(EPSILON, 1.0 - EPSILON);
+    mean(-1.0 * (truth.clone() * prediction.ln() + (1.0 - truth) * (1.0 - prediction).ln()))
+}
+
+// The prediction is a logit, i.e. the input to a sigmoid. This is more numerically stable than
+// applying a sigmoid and using binary_cross_entropy.
+pub fn binary_cross_entropy_with_logits(
+    prediction: algebra::Expr,
+    truth: algebra::Expr,
+) -> algebra::Expr {
+    mean(prediction.softplus() - prediction * truth)
+}
+
+// Focal loss is a binary cross entropy that down-weights easy examples, from "Focal Loss for Dense
+// Object Detection" (Lin et al.). The prediction is a probability. Alpha weights the positive
+// class and gamma controls how strongly easy examples are down-weighted. With an alpha of 0.5 and
+// a gamma of 0, it's half of binary_cross_entropy.
+pub fn focal_loss(
+    alpha: f32,
+    gamma: f32,
+) -> impl Fn(algebra::Expr, algebra::Expr) -> algebra::Expr {
+    move |prediction, truth| {
+        let prediction = prediction.clip(EPSILON, 1.0 - EPSILON);
+        let p_t = truth.clone() * prediction.clone() + (1.0 - truth.clone()) * (1.0 - prediction);
+        let alpha_t = truth.clone() * alpha + (1.0 - truth) * (1.0 - alpha);
+        mean(-1.0 * alpha_t * (1.0 - p_t.clone()).pow(gamma) * p_t.ln())
+    }
+}
+
+// This is the euclidean distance used by PyTorch's pairwise_distance. The small offset keeps the
+// gradient finite when the inputs are equal.
+fn pairwise_distance(a: algebra::Expr, b: algebra::Expr) -> algebra::Expr {
+    (a - b + 1e-6).square().sum().sqrt()
+}
+
+// Triplet margin loss pulls the anchor towards the positive and pushes it away from the negative
+// until they're separated by at least the margin. Each argument is an embedding.
+pub fn triplet_margin_loss(
+    margin: f32,
+) -> impl Fn(algebra::Expr, algebra::Expr, algebra::Expr) -> algebra::Expr {
+    move |anchor, positive, negative| {
+        algebra::relu(
+            pairwise_distance(anchor.clone(), positive) - pairwise_distance(anchor, negative)
+                + margin,
+        )
+    }
+}
+
+// Contrastive loss pulls similar embeddings together and pushes dissimilar embeddings at least the
+// margin apart. The truth is 1 for similar pairs and 0 for dissimilar pairs.
+pub fn contrastive_loss(
+    margin: f32,
+) -> impl Fn(algebra::Expr, algebra::Expr, algebra::Expr) -> algebra::Expr {
+    move |a, b, truth| {
+        let squared_distance = (a.clone() - b.clone()).square().sum();
+        truth.clone() * squared_distance
+            + (1.0 - truth) * algebra::relu(margin - pairwise_distance(a, b)).square()
+    }
+}
+
+// The truth should be -1 or 1.
+pub fn hinge(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    mean(algebra::relu(1.0 - truth * prediction))
+}
+
+// The truth should be -1 or 1.
+pub fn squared_hinge(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    mean(algebra::relu(1.0 - truth * prediction).square())
+}
+
+pub fn kl_divergence(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    let prediction = prediction.clip(EPSILON, 1.0);
+    let truth = truth.clip(EPSILON, 1.0);
+    (truth.clone() * (truth / prediction).ln()).sum()
+}
+
+pub fn poisson(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    mean(prediction.clone() - truth * (prediction + EPSILON).ln())
+}
+
+// This is the negative cosine similarity, so minimizing it makes the prediction and truth point
+// in the same direction.
+pub fn cosine_similarity(prediction: algebra::Expr, truth: algebra::Expr) -> algebra::Expr {
+    let norm = |e: &algebra::Expr| e.square().sum().clip(1e-12, f32::MAX).sqrt();
+    -1.0 * (prediction.clone() * truth.clone()).sum() / (norm(&prediction) * norm(&truth))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    fn check<L: Fn(algebra::Expr, algebra::Expr) -> algebra::Expr>(
+        loss: L,
+        prediction: &[f32],
+        truth: &[f32],
+        expected: f32,
+    ) {
+        let actual = *loss(
+            algebra::expr(ndarray::arr1(prediction)),
+            algebra::expr(ndarray::arr1(truth)),
+        )
+        .eval()
+        .first()
+        .unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    // import tensorflow as tf
+    // y_true = tf.constant([0.0, 1.0, 1.0, 0.0])
+    // y_pred = tf.constant([0.1, 0.8, 0.4, 0.3])
+
+    #[test]
+    fn test_regression() {
+        let (prediction, truth) = (&[0.1, 0.8, 0.4, 0.3], &[0.0, 1.0, 1.0, 0.0]);
+
+        // tf.keras.losses.mean_squared_error(y_true, y_pred)
+        check(mean_squared_error, prediction, truth, 0.125);
+
+        // tf.keras.losses.mean_absolute_error(y_true, y_pred)
+        check(mean_absolute_error, prediction, truth, 0.3);
+
+        // tf.keras.losses.Huber(delta=1.0)(y_true, y_pred)
+        check(huber(1.0), prediction, truth, 0.0625);
+
+        // tf.keras.losses.Huber(delta=0.3)(y_true, y_pred)
+        check(huber(0.3), prediction, truth, 0.05125);
+        check(smooth_l1(0.3), prediction, truth, 0.05125 / 0.3);
+
+        // tf.keras.losses.log_cosh(y_true, y_pred)
+        check(log_cosh, prediction, truth, 0.05983395);
+
+        // tf.keras.losses.binary_crossentropy(y_true, y_pred)
+        check(binary_cross_entropy, prediction, truth, 0.4003673);
+    }
+
+    #[test]
+    fn test_gradients() {
+        let prediction = algebra::v(
+            "p",
+            Rc::new(algebra::VariableValue::new(ndarray::arr1(&[
+                0.1, 0.8, 0.4, 0.3,
+            ]))),
+        );
+        let truth = algebra::expr(ndarray::arr1(&[0.0, 1.0, 1.0, 0.0]));
+        assert!(mean_squared_error(prediction, truth.clone())
+            .gradient("p")
+            .eval()
+            .all_close(&ndarray::arr1(&[0.05, -0.1, -0.3, 0.15]).into_dyn(), 1e-6));
+
+        // tf.gradients(tf.keras.losses.binary_crossentropy(y_true, logits, from_logits=True), logits)
+        let logits = algebra::v(
+            "l",
+            Rc::new(algebra::VariableValue::new(ndarray::arr1(&[
+                -2.0, 1.5, 0.0, 3.0,
+            ]))),
+        );
+        let loss = binary_cross_entropy_with_logits(logits, truth);
+        assert!((loss.eval().first().unwrap() - 1.017519).abs() < 1e-6);
+        assert!(loss.gradient("l").eval().all_close(
+            &ndarray::arr1(&[0.02980073, -0.04560638, -0.125, 0.2381435]).into_dyn(),
+            1e-6
+        ));
+    }
+
+    #[test]
+    fn test_classification() {
+        // tf.keras.losses.hinge([-1.0, 1.0, 1.0, -1.0], [-0.5, 2.0, 0.3, 0.2])
+        check(hinge, &[-0.5, 2.0, 0.3, 0.2], &[-1.0, 1.0, 1.0, -1.0], 0.6);
+
+        // tf.keras.losses.squared_hinge([-1.0, 1.0, 1.0, -1.0], [-0.5, 2.0, 0.3, 0.2])
+        check(
+            squared_hinge,
+            &[-0.5, 2.0, 0.3, 0.2],
+            &[-1.0, 1.0, 1.0, -1.0],
+            0.545,
+        );
+
+        // tf.keras.losses.sparse_categorical_crossentropy([1.0], [[0.1, 0.7, 0.2]])
+        assert!(sparse_categorical_cross_entropy(
+            algebra::expr(ndarray::arr1(&[0.1, 0.7, 0.2])),
+            algebra::expr(1.0),
+        )
+        .eval()
+        .all_close(&ndarray::arr0(0.3566749).into_dyn(), 1e-6));
+    }
+
+    #[test]
+    fn test_focal_loss() {
+        // import tensorflow_addons as tfa
+        // tfa.losses.sigmoid_focal_crossentropy(y_true, y_pred, alpha=0.25, gamma=2.0) / 4
+        check(
+            focal_loss(0.25, 2.0),
+            &[0.1, 0.8, 0.4, 0.3],
+            &[0.0, 1.0, 1.0, 0.0],
+            0.02739084,
+        );
+        check(
+            focal_loss(0.5, 0.0),
+            &[0.1, 0.8, 0.4, 0.3],
+            &[0.0, 1.0, 1.0, 0.0],
+            0.4003673 / 2.0,
+        );
+    }
+
+    #[test]
+    fn test_metric_learning() {
+        // import torch
+        // a = torch.tensor([[0.0, 1.0]])
+        // p = torch.tensor([[0.5, 1.0]])
+        // n = torch.tensor([[1.0, 2.0]])
+        // torch.nn.TripletMarginLoss(margin=1.0)(a, p, n)
+        let loss = triplet_margin_loss(1.0)(
+            algebra::expr(ndarray::arr1(&[0.0, 1.0])),
+            algebra::expr(ndarray::arr1(&[0.5, 1.0])),
+            algebra::expr(ndarray::arr1(&[1.0, 2.0])),
+        );
+        assert!((loss.eval().first().unwrap() - 0.08578685).abs() < 1e-6);
+
+        let a = algebra::v(
+            "a",
+            Rc::new(algebra::VariableValue::new(ndarray::arr1(&[1.0, 0.0]))),
+        );
+        let b = algebra::expr(ndarray::arr1(&[0.0, 0.0]));
+        let similar = contrastive_loss(1.5)(a.clone(), b.clone(), algebra::expr(1.0));
+        assert_eq!(similar.eval(), ndarray::arr0(1.0).into_dyn());
+        assert!(similar
+            .gradient("a")
+            .eval()
+            .all_close(&ndarray::arr1(&[2.0, 0.0]).into_dyn(), 1e-6));
+        let dissimilar = contrastive_loss(1.5)(a, b, algebra::expr(0.0));
+        assert!((dissimilar.eval().first().unwrap() - 0.25).abs() < 1e-5);
+        assert!(dissimilar
+            .gradient("a")
+            .eval()
+            .all_close(&ndarray::arr1(&[-1.0, 0.0]).into_dyn(), 1e-5));
+    }
+
+    #[test]
+    fn test_label_smoothing() {
+        // tf.keras.losses.categorical_crossentropy([0.0, 1.0, 0.0], [0.1, 0.7, 0.2], label_smoothing=0.3)
+        check(
+            label_smoothing(0.3, categorical_cross_entropy),
+            &[0.1, 0.7, 0.2],
+            &[0.0, 1.0, 0.0],
+            0.6765423,
+        );
+
+        // tf.keras.losses.binary_crossentropy(y_true, y_pred, label_smoothing=0.2)
+        check(
+            binary_label_smoothing(0.2, binary_cross_entropy),
+            &[0.1, 0.8, 0.4, 0.3],
+            &[0.0, 1.0, 1.0, 0.0],
+            0.501001,
+        );
+    }
+
+    #[test]
+    fn test_reduce() {
+        let losses = [1.0, 2.0, 6.0];
+        assert_eq!(
+            reduce(&losses, Reduction::None),
+            ndarray::arr1(&[1.0, 2.0, 6.0]).into_dyn()
+        );
+        assert_eq!(
+            reduce(&losses, Reduction::Sum),
+            ndarray::arr0(9.0).into_dyn()
+        );
+        assert_eq!(
+            reduce(&losses, Reduction::Mean),
+            ndarray::arr0(3.0).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_distributions() {
+        // tf.keras.losses.kl_divergence([0.2, 0.5, 0.3], [0.1, 0.6, 0.3])
+        check(
+            kl_divergence,
+            &[0.1, 0.6, 0.3],
+            &[0.2, 0.5, 0.3],
+            0.04746866,
+        );
+
+        // tf.keras.losses.poisson([1.0, 0.0, 3.0], [1.5, 0.5, 2.0])
+        check(poisson, &[1.5, 0.5, 2.0], &[1.0, 0.0, 3.0], 0.505031);
+
+        // tf.keras.losses.cosine_similarity([1.0, 2.0, 0.0], [2.0, 1.0, 1.0])
+        check(
+            cosine_similarity,
+            &[2.0, 1.0, 1.0],
+            &[1.0, 2.0, 0.0],
+            -0.7302967,
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use std::collections::BTreeMap;
+
+// Metrics are named results of an evaluation, e.g. "loss" or "val_accuracy".
+pub type Logs = BTreeMap<String, f32>;
+
+// A metric is accumulated over samples and doesn't affect training.
+pub trait Metric {
+    fn name(&self) -> String;
+
+    fn reset(&mut self);
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>);
+
+    fn result(&self) -> f32;
+}
+
+fn argmax(a: &ndarray::ArrayViewD<f32>) -> usize {
+    let mut selection = 0;
+    let mut selection_score = f32::NEG_INFINITY;
+    for (i, &v) in a.iter().enumerate() {
+        if v > selection_score {
+            selection = i;
+            selection_score = v;
+        }
+    }
+    selection
+}
+
+// Returns the class of a target. Single-element targets are class indices, or binary labels.
+// Otherwise they're one-hot.
+pub(crate) fn target_class(target: &ndarray::ArrayViewD<f32>) -> usize {
+    if target.len() == 1 {
+        target.first().unwrap().round() as usize
+    } else {
+        argmax(target)
+    }
+}
+
+// Returns the class of a prediction. Single-element predictions are probabilities of class 1.
+// Otherwise they're scores for each class.
+fn predicted_class(prediction: &ndarray::ArrayViewD<f32>) -> usize {
+    if prediction.len() == 1 {
+        (*prediction.first().unwrap() >= 0.5) as usize
+    } else {
+        argmax(prediction)
+    }
+}
+
+// Returns the predicted score for a class.
+fn class_score(prediction: &ndarray::ArrayViewD<f32>, class: usize) -> f32 {
+    if prediction.len() == 1 {
+        let p = *prediction.first().unwrap();
+        if class == 1 {
+            p
+        } else {
+            1.0 - p
+        }
+    } else {
+        prediction
+            .iter()
+            .nth(class)
+            .cloned()
+            .unwrap_or(f32::NEG_INFINITY)
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f32 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f32 / denominator as f32
+    }
+}
+
+// The fraction of samples whose predicted class is the target class.
+#[derive(Default)]
+pub struct Accuracy {
+    correct: usize,
+    total: usize,
+}
+
+impl Accuracy {
+    pub fn new() -> Accuracy {
+        Default::default()
+    }
+}
+
+impl Metric for Accuracy {
+    fn name(&self) -> String {
+        "accuracy".to_string()
+    }
+
+    fn reset(&mut self) {
+        *self = Default::default();
+    }
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>) {
+        if predicted_class(&prediction) == target_class(&target) {
+            self.correct += 1;
+        }
+        self.total += 1;
+    }
+
+    fn result(&self) -> f32 {
+        ratio(self.correct, self.total)
+    }
+}
+
+// The fraction of samples whose target class is among the k highest scoring predictions.
+pub struct TopKAccuracy {
+    k: usize,
+    correct: usize,
+    total: usize,
+}
+
+impl TopKAccuracy {
+    pub fn new(k: usize) -> TopKAccuracy {
+        TopKAccuracy {
+            k,
+            correct: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Metric for TopKAccuracy {
+    fn name(&self) -> String {
+        format!("top_{}_accuracy", self.k)
+    }
+
+    fn reset(&mut self) {
+        self.correct = 0;
+        self.total = 0;
+    }
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>) {
+        let score = class_score(&prediction, target_class(&target));
+        if prediction.iter().filter(|&&v| v > score).count() < self.k {
+            self.correct += 1;
+        }
+        self.total += 1;
+    }
+
+    fn result(&self) -> f32 {
+        ratio(self.correct, self.total)
+    }
+}
+
+// Counts of predictions for one class, treated as the positive class.
+#[derive(Default)]
+struct ConfusionCounts {
+    true_positives: usize,
+    false_positives: usize,
+    false_negatives: usize,
+}
+
+impl ConfusionCounts {
+    fn update(
+        &mut self,
+        class: usize,
+        prediction: ndarray::ArrayViewD<f32>,
+        target: ndarray::ArrayViewD<f32>,
+    ) {
+        let predicted = predicted_class(&prediction) == class;
+        let actual = target_class(&target) == class;
+        match (predicted, actual) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, true) => self.false_negatives += 1,
+            (false, false) => {}
+        }
+    }
+
+    fn precision(&self) -> f32 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    fn recall(&self) -> f32 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+}
+
+// The fraction of predictions of a class that are correct. For binary predictions, the class is
+// usually 1. The metrics that take a class include it in their names, e.g. "precision_1".
+pub struct Precision {
+    class: usize,
+    counts: ConfusionCounts,
+}
+
+impl Precision {
+    pub fn new(class: usize) -> Precision {
+        Precision {
+            class,
+            counts: Default::default(),
+        }
+    }
+}
+
+impl Metric for Precision {
+    fn name(&self) -> String {
+        format!("precision_{}", self.class)
+    }
+
+    fn reset(&mut self) {
+        self.counts = Default::default();
+    }
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>) {
+        self.counts.update(self.class, prediction, target);
+    }
+
+    fn result(&self) -> f32 {
+        self.counts.precision()
+    }
+}
+
+// The fraction of samples of a class that are predicted correctly.
+pub struct Recall {
+    class: usize,
+    counts: ConfusionCounts,
+}
+
+impl Recall {
+    pub fn new(class: usize) -> Recall {
+        Recall {
+            class,
+            counts: Default::default(),
+        }
+    }
+}
+
+impl Metric for Recall {
+    fn name(&self) -> String {
+        format!("recall_{}", self.class)
+    }
+
+    fn reset(&mut self) {
+        self.counts = Default::default();
+    }
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>) {
+        self.counts.update(self.class, prediction, target);
+    }
+
+    fn result(&self) -> f32 {
+        self.counts.recall()
+    }
+}
+
+// The harmonic mean of precision and recall.
+pub struct F1Score {
+    class: usize,
+    counts: ConfusionCounts,
+}
+
+impl F1Score {
+    pub fn new(class: usize) -> F1Score {
+        F1Score {
+            class,
+            counts: Default::default(),
+        }
+    }
+}
+
+impl Metric for F1Score {
+    fn name(&self) -> String {
+        format!("f1_score_{}", self.class)
+    }
+
+    fn reset(&mut self) {
+        self.counts = Default::default();
+    }
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>) {
+        self.counts.update(self.class, prediction, target);
+    }
+
+    fn result(&self) -> f32 {
+        let (precision, recall) = (self.counts.precision(), self.counts.recall());
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+}
+
+// The area under the ROC curve for a class. This is computed exactly from the scores of every
+// sample, as the probability that a positive sample is scored higher than a negative one.
+pub struct AUC {
+    class: usize,
+    scores: Vec<(f32, bool)>,
+}
+
+impl AUC {
+    pub fn new(class: usize) -> AUC {
+        AUC {
+            class,
+            scores: Vec::new(),
+        }
+    }
+}
+
+impl Metric for AUC {
+    fn name(&self) -> String {
+        format!("auc_{}", self.class)
+    }
+
+    fn reset(&mut self) {
+        self.scores.clear();
+    }
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>) {
+        self.scores.push((
+            class_score(&prediction, self.class),
+            target_class(&target) == self.class,
+        ));
+    }
+
+    fn result(&self) -> f32 {
+        let mut scores = self.scores.clone();
+        scores.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        // sum the ranks of the positive samples, giving tied scores their average rank
+        let mut positive_rank_sum = 0.0;
+        let mut i = 0;
+        while i < scores.len() {
+            let mut j = i;
+            while j < scores.len() && scores[j].0 == scores[i].0 {
+                j += 1;
+            }
+            let rank = (i + j + 1) as f64 / 2.0;
+            positive_rank_sum += rank * scores[i..j].iter().filter(|s| s.1).count() as f64;
+            i = j;
+        }
+
+        let positives = scores.iter().filter(|s| s.1).count() as f64;
+        let negatives = scores.len() as f64 - positives;
+        if positives == 0.0 || negatives == 0.0 {
+            return 0.0;
+        }
+        ((positive_rank_sum - positives * (positives + 1.0) / 2.0) / (positives * negatives)) as f32
+    }
+}
+
+// The mean of the squared differences between each element of the predictions and targets.
+#[derive(Default)]
+pub struct MeanSquaredError {
+    sum: f64,
+    count: usize,
+}
+
+impl MeanSquaredError {
+    pub fn new() -> MeanSquaredError {
+        Default::default()
+    }
+}
+
+impl Metric for MeanSquaredError {
+    fn name(&self) -> String {
+        "mean_squared_error".to_string()
+    }
+
+    fn reset(&mut self) {
+        *self = Default::default();
+    }
+
+    fn update(&mut self, prediction: ndarray::ArrayViewD<f32>, target: ndarray::ArrayViewD<f32>) {
+        for (p, t) in prediction.iter().zip(target.iter()) {
+            self.sum += ((p - t) as f64).powi(2);
+        }
+        self.count += prediction.len();
+    }
+
+    fn result(&self) -> f32 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.sum / self.count as f64) as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate<M: Metric>(mut metric: M, predictions: &[&[f32]], targets: &[&[f32]]) -> f32 {
+        for (p, t) in predictions.iter().zip(targets.iter()) {
+            metric.update(ndarray::aview1(p).into_dyn(), ndarray::aview1(t).into_dyn());
+        }
+        let result = metric.result();
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+        result
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let predictions: &[&[f32]] = &[&[0.1, 0.7, 0.2], &[0.6, 0.3, 0.1], &[0.2, 0.3, 0.5]];
+        let targets: &[&[f32]] = &[&[0.0, 1.0, 0.0], &[0.0, 0.0, 1.0], &[2.0]];
+        assert_eq!(evaluate(Accuracy::new(), predictions, targets), 2.0 / 3.0);
+        assert_eq!(
+            evaluate(TopKAccuracy::new(1), predictions, targets),
+            2.0 / 3.0
+        );
+        assert_eq!(
+            evaluate(TopKAccuracy::new(2), predictions, targets),
+            2.0 / 3.0
+        );
+        assert_eq!(evaluate(TopKAccuracy::new(3), predictions, targets), 1.0);
+
+        // negative scores shouldn't be mistaken for class 0
+        assert_eq!(
+            evaluate(Accuracy::new(), &[&[-2.0, -1.0]], &[&[0.0, 1.0]]),
+            1.0
+        );
+
+        // binary predictions are thresholded
+        assert_eq!(
+            evaluate(
+                Accuracy::new(),
+                &[&[0.6], &[0.4], &[0.3]],
+                &[&[1.0], &[1.0], &[0.0]]
+            ),
+            2.0 / 3.0
+        );
+    }
+
+    #[test]
+    fn test_precision_recall() {
+        let predictions: &[&[f32]] = &[&[0.9], &[0.8], &[0.7], &[0.2], &[0.1]];
+        let targets: &[&[f32]] = &[&[1.0], &[0.0], &[1.0], &[1.0], &[0.0]];
+        assert_eq!(evaluate(Precision::new(1), predictions, targets), 2.0 / 3.0);
+        assert_eq!(evaluate(Recall::new(1), predictions, targets), 2.0 / 3.0);
+        assert_eq!(evaluate(F1Score::new(1), predictions, targets), 2.0 / 3.0);
+        assert_eq!(evaluate(Precision::new(0), predictions, targets), 0.5);
+        assert_eq!(evaluate(Recall::new(0), predictions, targets), 0.5);
+    }
+
+    #[test]
+    fn test_class_names() {
+        // metrics for different classes can be logged together
+        let mut metrics: Vec<Box<dyn Metric>> = vec![
+            Box::new(Precision::new(0)),
+            Box::new(Precision::new(1)),
+            Box::new(Recall::new(0)),
+            Box::new(Recall::new(1)),
+            Box::new(F1Score::new(0)),
+            Box::new(F1Score::new(1)),
+            Box::new(AUC::new(0)),
+            Box::new(AUC::new(1)),
+        ];
+        let predictions: &[&[f32]] = &[&[0.9], &[0.8], &[0.7], &[0.2], &[0.1]];
+        let targets: &[&[f32]] = &[&[1.0], &[0.0], &[1.0], &[1.0], &[0.0]];
+        for metric in metrics.iter_mut() {
+            for (p, t) in predictions.iter().zip(targets.iter()) {
+                metric.update(ndarray::aview1(p).into_dyn(), ndarray::aview1(t).into_dyn());
+            }
+        }
+        let logs: Logs = metrics.iter().map(|m| (m.name(), m.result())).collect();
+        assert_eq!(logs.len(), metrics.len());
+        assert_eq!(logs["precision_0"], 0.5);
+        assert_eq!(logs["precision_1"], 2.0 / 3.0);
+        assert_eq!(logs["recall_0"], 0.5);
+        assert_eq!(logs["recall_1"], 2.0 / 3.0);
+    }
+
+    #[test]
+    fn test_auc() {
+        // 4 of the 6 positive-negative pairs are ordered correctly
+        let predictions: &[&[f32]] = &[&[0.9], &[0.8], &[0.7], &[0.2], &[0.1]];
+        let targets: &[&[f32]] = &[&[1.0], &[0.0], &[1.0], &[1.0], &[0.0]];
+        assert_eq!(evaluate(AUC::new(1), predictions, targets), 4.0 / 6.0);
+
+        // ties count as half
+        assert_eq!(
+            evaluate(AUC::new(1), &[&[0.5], &[0.5]], &[&[1.0], &[0.0]]),
+            0.5
+        );
+
+        // multi-class predictions use the score of the class
+        assert_eq!(
+            evaluate(
+                AUC::new(0),
+                &[&[0.6, 0.4], &[0.3, 0.7]],
+                &[&[1.0, 0.0], &[0.0, 1.0]]
+            ),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_mean_squared_error() {
+        assert_eq!(
+            evaluate(
+                MeanSquaredError::new(),
+                &[&[1.0, 2.0], &[3.0, 4.0]],
+                &[&[1.0, 0.0], &[0.0, 4.0]]
+            ),
+            13.0 / 4.0
+        );
+    }
+}
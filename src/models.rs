@@ -1,56 +1,204 @@
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::io::{Read, Write};
 use std::rc::Rc;
 
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
 
-use super::{algebra, graph, Dataset, Layer};
+use super::{
+    algebra, callbacks, clipping, constraints, graph, initializers, losses, metrics, schedules,
+    Dataset, Layer, LayerInstance, LayerVariable,
+};
+
+// Variable names are used to identify variables outside of the graph, e.g. in checkpoints, so they
+// must be unique within a model.
+fn check_variable_names(variables: &[LayerVariable]) -> Result<(), Box<dyn Error>> {
+    let mut names = HashSet::new();
+    for v in variables {
+        if !names.insert(v.name.as_str()) {
+            bail!("duplicate variable name: {}", v.name);
+        }
+    }
+    Ok(())
+}
 
 // Sequential is used to build a neural network based on layers that are activated in sequence.
+// Layers are initialized as they're added.
 pub struct Sequential {
     input_shape: ndarray::IxDyn,
-    layers: Vec<Box<dyn Layer>>,
+    output_shape: ndarray::IxDyn,
+    layers: Vec<SequentialLayer>,
+    seed: u64,
+}
+
+struct SequentialLayer {
+    name: String,
+    type_name: String,
+    output_shape: ndarray::IxDyn,
+    instance: Box<dyn LayerInstance>,
+}
+
+// Formats a number with thousands separators.
+// usize::is_multiple_of is too new for the Rust versions this crate supports.
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+fn format_bytes(n: usize) -> String {
+    if n < 1024 {
+        format!("{} B", n)
+    } else if n < 1024 * 1024 {
+        format!("{:.2} KB", n as f64 / 1024.0)
+    } else {
+        format!("{:.2} MB", n as f64 / (1024.0 * 1024.0))
+    }
 }
 
 impl Sequential {
     pub fn new<D: ndarray::Dimension>(input_shape: D) -> Sequential {
+        let input_shape = input_shape.into_dyn();
         Sequential {
-            input_shape: input_shape.clone().into_dyn(),
+            output_shape: input_shape.clone(),
+            input_shape,
             layers: Vec::new(),
+            seed: 0,
         }
     }
 
+    // Sets the seed used for random initialization of layers that are added afterwards. Each layer
+    // derives its own stream from it.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    // Adds a layer named "l{index}".
     pub fn add_layer<L: Layer + 'static>(&mut self, layer: L) -> Result<(), Box<dyn Error>> {
-        self.layers.push(Box::new(layer));
+        let name = format!("l{}", self.layers.len());
+        self.add_named_layer(name, layer)
+    }
+
+    // Adds a layer with the given name. The name is used as the namespace for the layer's
+    // variables, so it must be unique within the model.
+    pub fn add_named_layer<S: Into<String>, L: Layer + 'static>(
+        &mut self,
+        name: S,
+        layer: L,
+    ) -> Result<(), Box<dyn Error>> {
+        let name = name.into();
+        if self.layers.iter().any(|l| l.name == name) {
+            bail!("duplicate layer name: {}", name);
+        }
+        let layer: Box<dyn Layer> = Box::new(layer);
+        let type_name = layer.type_name();
+        initializers::set_seed(self.seed);
+        let instance = layer.init(&name, &self.output_shape);
+        self.output_shape = instance.output_shape(&self.output_shape);
+        self.layers.push(SequentialLayer {
+            name,
+            type_name,
+            output_shape: self.output_shape.clone(),
+            instance,
+        });
         Ok(())
     }
 
-    pub fn compile_for_inference(mut self) -> CompiledInferenceSequential {
+    // Returns a table describing each layer and the model's parameters, suitable for printing.
+    pub fn summary(&self) -> String {
+        let mut rows = vec![(
+            "Layer (type)".to_string(),
+            "Output Shape".to_string(),
+            "Param #".to_string(),
+        )];
+        let (mut trainable, mut non_trainable) = (0, 0);
+        for layer in self.layers.iter() {
+            let mut params = 0;
+            for v in layer.instance.variables() {
+                let size = ndarray::Dimension::size(&v.value.shape());
+                params += size;
+                if v.trainable {
+                    trainable += size;
+                } else {
+                    non_trainable += size;
+                }
+            }
+            rows.push((
+                format!("{} ({})", layer.name, layer.type_name),
+                format!("{:?}", ndarray::Dimension::slice(&layer.output_shape)),
+                format_count(params),
+            ));
+        }
+        let widths = rows.iter().fold((0, 0, 0), |w, r| {
+            (w.0.max(r.0.len()), w.1.max(r.1.len()), w.2.max(r.2.len()))
+        });
+        let total_width = widths.0 + widths.1 + widths.2 + 4;
+        let mut result = String::new();
+        for (i, row) in rows.iter().enumerate() {
+            result += &format!(
+                "{:w0$}  {:w1$}  {:>w2$}\n",
+                row.0,
+                row.1,
+                row.2,
+                w0 = widths.0,
+                w1 = widths.1,
+                w2 = widths.2
+            );
+            if i == 0 {
+                result += &"=".repeat(total_width);
+                result.push('\n');
+            }
+        }
+        result += &"=".repeat(total_width);
+        result.push('\n');
+        let total = trainable + non_trainable;
+        result += &format!(
+            "Total params: {} ({})\n",
+            format_count(total),
+            format_bytes(total * std::mem::size_of::<f32>())
+        );
+        result += &format!("Trainable params: {}\n", format_count(trainable));
+        result += &format!("Non-trainable params: {}\n", format_count(non_trainable));
+        result
+    }
+
+    pub fn compile_for_inference(self) -> Result<CompiledInferenceSequential, Box<dyn Error>> {
         let input_value = Rc::new(algebra::VariableValue::new(ndarray::Array::zeros(
             self.input_shape,
         )));
         let input = algebra::v("i", input_value.clone());
         let mut output = input.clone();
-        for (i, layer) in self.layers.drain(..).enumerate() {
-            let instance = layer.init(format!("l{}", i).as_str(), &output.shape());
-            output = instance.expression(output);
+        let mut variables = Vec::new();
+        for layer in self.layers.iter() {
+            variables.extend_from_slice(layer.instance.variables());
+            output = layer.instance.expression(output);
         }
+        check_variable_names(&variables)?;
         let mut graph = graph::Graph::new();
         let output_node_id = graph.add(output);
-        CompiledInferenceSequential {
+        Ok(CompiledInferenceSequential {
             input: input_value,
             graph: graph,
             output_node_id: output_node_id,
-        }
+        })
     }
 
     // Once the model is final, it needs to be "compiled" before it can do much. This just does a
     // bit of math up front before returning the object that can be used for training or inference.
     pub fn compile_for_training<D, L>(
-        mut self,
+        self,
         target_shape: D,
         loss_function: L,
-    ) -> CompiledTrainingSequential
+    ) -> Result<CompiledTrainingSequential, Box<dyn Error>>
     where
         D: ndarray::Dimension,
         L: Fn(algebra::Expr, algebra::Expr) -> algebra::Expr + 'static,
@@ -60,42 +208,83 @@ impl Sequential {
         )));
         let input = algebra::v("i", input_value.clone());
         let mut output = input.clone();
-        let mut trainable_variables = Vec::new();
-        for (i, layer) in self.layers.drain(..).enumerate() {
-            let instance = layer.init(format!("l{}", i).as_str(), &output.shape());
-            for v in instance.variables() {
-                trainable_variables.push(TrainableVariable {
-                    name: v.name.clone(),
-                    value: v.value.clone(),
-                    gradient_node_id: 0,
-                });
-            }
-            output = instance.expression(output);
+        let mut variables = Vec::new();
+        let mut regularization_losses = Vec::new();
+        for layer in self.layers.iter() {
+            variables.extend_from_slice(layer.instance.variables());
+            regularization_losses.extend_from_slice(layer.instance.regularization_losses());
+            output = layer.instance.expression(output);
         }
+        check_variable_names(&variables)?;
         let target_value = Rc::new(algebra::VariableValue::new(ndarray::Array::zeros(
             target_shape,
         )));
         let target = algebra::v("t", target_value.clone());
-        let loss = loss_function(output.clone(), target);
+        let weight_value = Rc::new(algebra::VariableValue::new(ndarray::arr0(1.0)));
+        let sample_loss =
+            loss_function(output.clone(), target) * algebra::v("sw", weight_value.clone());
+
+        // the penalties are also kept on their own so that loss can count them once for the whole
+        // dataset rather than once per sample
+        let regularization_loss =
+            regularization_losses
+                .into_iter()
+                .fold(None, |sum: Option<algebra::Expr>, penalty| match sum {
+                    Some(sum) => Some(sum + penalty),
+                    None => Some(penalty),
+                });
+        let loss = match &regularization_loss {
+            Some(penalty) => sample_loss.clone() + penalty.clone(),
+            None => sample_loss.clone(),
+        };
         let mut graph = graph::Graph::new();
-        let gradients = loss.gradients();
-        for tv in trainable_variables.iter_mut() {
-            tv.gradient_node_id = graph.add(gradients.get(&tv.name).unwrap().clone());
-        }
+        let trainable: Vec<_> = variables.iter().filter(|v| v.trainable).cloned().collect();
+        let gradients = loss.gradients_wrt(
+            &trainable
+                .iter()
+                .map(|v| algebra::v(v.name.clone(), v.value.clone()))
+                .collect::<Vec<_>>(),
+        );
+        let trainable_variables = trainable
+            .into_iter()
+            .zip(gradients)
+            .map(|(v, gradient)| TrainableVariable {
+                name: v.name,
+                value: v.value,
+                constraint: v.constraint,
+                gradient_node_id: graph.add(gradient),
+            })
+            .collect();
         let output_node_id = graph.add(output);
-        CompiledTrainingSequential {
+        let loss_node_id = graph.add(loss);
+        let sample_loss_node_id = graph.add(sample_loss);
+        let regularization_loss_node_id = regularization_loss.map(|penalty| graph.add(penalty));
+        Ok(CompiledTrainingSequential {
             input: input_value,
             target: target_value,
+            weight: weight_value,
+            class_weights: HashMap::new(),
+            metrics: Vec::new(),
+            gradient_clipping: Default::default(),
+            gradient_accumulation_steps: 1,
+            learning_rate: 0.0,
+            learning_rate_override: None,
+            stop_training: false,
+            variables,
             trainable_variables: trainable_variables,
             graph: graph,
             output_node_id: output_node_id,
-        }
+            loss_node_id,
+            sample_loss_node_id,
+            regularization_loss_node_id,
+        })
     }
 }
 
 struct TrainableVariable {
     name: String,
     value: Rc<algebra::VariableValue>,
+    constraint: Option<constraints::Constraint>,
     gradient_node_id: usize,
 }
 
@@ -120,72 +309,566 @@ impl CompiledInferenceSequential {
 pub struct CompiledTrainingSequential {
     input: Rc<algebra::VariableValue>,
     target: Rc<algebra::VariableValue>,
+    weight: Rc<algebra::VariableValue>,
+    class_weights: HashMap<usize, f32>,
+    metrics: Vec<Box<dyn metrics::Metric>>,
+    gradient_clipping: clipping::GradientClipping,
+    gradient_accumulation_steps: usize,
+    learning_rate: f32,
+    learning_rate_override: Option<f32>,
+    stop_training: bool,
+    variables: Vec<LayerVariable>,
     trainable_variables: Vec<TrainableVariable>,
     graph: graph::Graph,
     output_node_id: usize,
+    loss_node_id: usize,
+    // The loss without the regularization penalties, which have their own node if there are any.
+    sample_loss_node_id: usize,
+    regularization_loss_node_id: Option<usize>,
 }
 
-fn max_index<S, D>(a: &ndarray::ArrayBase<S, D>) -> usize
-where
-    S: ndarray::Data<Elem = f32>,
-    D: ndarray::Dimension,
-{
-    let mut selection = 0;
-    let mut selection_score = 0.0;
-    for (i, &v) in a
-        .view()
-        .into_dimensionality::<ndarray::Ix1>()
-        .unwrap()
-        .indexed_iter()
-    {
-        if v > selection_score {
-            selection = i;
-            selection_score = v;
+impl CompiledTrainingSequential {
+    // Sets a weight for each class. The loss for each sample is multiplied by the weight of its
+    // class, in addition to its sample weight. Classes without a weight have a weight of 1.
+    pub fn set_class_weights(&mut self, class_weights: HashMap<usize, f32>) {
+        self.class_weights = class_weights;
+    }
+
+    // Sets the metrics that fit reports at the end of each epoch, for both the training and
+    // validation data.
+    pub fn set_metrics(&mut self, metrics: Vec<Box<dyn metrics::Metric>>) {
+        self.metrics = metrics;
+    }
+
+    // Sets limits on the gradients used to update the variables during training.
+    pub fn set_gradient_clipping(&mut self, gradient_clipping: clipping::GradientClipping) {
+        self.gradient_clipping = gradient_clipping;
+    }
+
+    // Makes fit accumulate the gradients of this many samples, or micro-batches, before taking a
+    // step with their average. The last step of each epoch may average fewer of them. Learning rate
+    // schedules count steps, not micro-batches.
+    pub fn set_gradient_accumulation_steps(&mut self, steps: usize) -> Result<(), Box<dyn Error>> {
+        if steps == 0 {
+            bail!("gradient accumulation steps must be positive");
         }
+        self.gradient_accumulation_steps = steps;
+        Ok(())
     }
-    selection
-}
 
-impl CompiledTrainingSequential {
-    // Trains the model using stochastic gradient descent.
-    pub fn fit<D: Dataset>(
+    // Sets the target and weight inputs of the graph. The sample weight is multiplied by the weight
+    // of the target's class.
+    fn set_target<S, D>(&mut self, target: ndarray::ArrayBase<S, D>, sample_weight: f32)
+    where
+        S: ndarray::Data<Elem = f32>,
+        D: ndarray::Dimension,
+    {
+        let target = target.into_dyn();
+        let class_weight = match self
+            .class_weights
+            .get(&metrics::target_class(&target.view()))
+        {
+            Some(&w) => w,
+            None => 1.0,
+        };
+        (*self.target).set(target);
+        (*self.weight).set(ndarray::arr0(class_weight * sample_weight));
+    }
+
+    // Sets the inputs of the graph for a sample from the dataset.
+    fn set_sample<D: Dataset + ?Sized>(
         &mut self,
         dataset: &mut D,
-        learning_rate: f32,
-        epochs: usize,
+        i: usize,
     ) -> Result<(), Box<dyn Error>> {
-        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
-        let gradient_node_ids: Vec<_> = self
+        let sample_weight = dataset.sample_weight(i)?;
+        self.set_target(dataset.target(i)?, sample_weight);
+        (*self.input).set(dataset.input(i)?);
+        Ok(())
+    }
+
+    // Returns the gradients computed by the last evaluation of the graph, in the order of the
+    // trainable variables.
+    fn gradients(&self) -> Vec<ndarray::ArrayD<f32>> {
+        self.trainable_variables
+            .iter()
+            .map(|tv| self.graph.node_output(tv.gradient_node_id).clone())
+            .collect()
+    }
+
+    // Returns the global norm of the gradients computed by the last evaluation of the graph.
+    fn gradient_norm(&self) -> f32 {
+        clipping::global_norm(
+            self.trainable_variables
+                .iter()
+                .map(|tv| self.graph.node_output(tv.gradient_node_id)),
+        )
+    }
+
+    fn update_variable(
+        tv: &TrainableVariable,
+        gradient: &ndarray::ArrayD<f32>,
+        learning_rate: f32,
+    ) {
+        let value = tv.value.get() - gradient * learning_rate;
+        tv.value.set(match &tv.constraint {
+            Some(constraint) => constraint(value),
+            None => value,
+        });
+    }
+
+    // Clips the gradients and takes a step of gradient descent with them. They must be in the order
+    // of the trainable variables.
+    fn update_variables(&mut self, mut gradients: Vec<ndarray::ArrayD<f32>>, learning_rate: f32) {
+        self.gradient_clipping.apply(&mut gradients);
+        for (tv, gradient) in self.trainable_variables.iter().zip(gradients.iter()) {
+            Self::update_variable(tv, gradient, learning_rate);
+        }
+    }
+
+    // Takes a step of gradient descent with the gradients computed by the last evaluation of the
+    // graph. They're only copied if they need to be clipped.
+    fn update_variables_from_graph(&mut self, learning_rate: f32) {
+        if self.gradient_clipping.is_enabled() {
+            self.update_variables(self.gradients(), learning_rate);
+        } else {
+            for tv in self.trainable_variables.iter() {
+                Self::update_variable(
+                    tv,
+                    self.graph.node_output(tv.gradient_node_id),
+                    learning_rate,
+                );
+            }
+        }
+    }
+
+    // Computes the loss for a single sample, without updating the model.
+    pub fn compute_loss<S1, D1, S2, D2>(
+        &mut self,
+        input: ndarray::ArrayBase<S1, D1>,
+        target: ndarray::ArrayBase<S2, D2>,
+    ) -> f32
+    where
+        S1: ndarray::Data<Elem = f32>,
+        D1: ndarray::Dimension,
+        S2: ndarray::Data<Elem = f32>,
+        D2: ndarray::Dimension,
+    {
+        self.set_target(target, 1.0);
+        (*self.input).set(input);
+        self.graph.eval_nodes(vec![self.loss_node_id]);
+        *self.graph.node_output(self.loss_node_id).first().unwrap()
+    }
+
+    // Computes the gradient of the loss for a single sample with respect to each trainable
+    // variable, without updating the model. The gradients are named after their variables.
+    pub fn compute_gradients<S1, D1, S2, D2>(
+        &mut self,
+        input: ndarray::ArrayBase<S1, D1>,
+        target: ndarray::ArrayBase<S2, D2>,
+    ) -> Vec<(String, ndarray::ArrayD<f32>)>
+    where
+        S1: ndarray::Data<Elem = f32>,
+        D1: ndarray::Dimension,
+        S2: ndarray::Data<Elem = f32>,
+        D2: ndarray::Dimension,
+    {
+        self.set_target(target, 1.0);
+        (*self.input).set(input);
+        self.graph.eval_nodes(
+            self.trainable_variables
+                .iter()
+                .map(|tv| tv.gradient_node_id)
+                .collect(),
+        );
+        self.trainable_variables
+            .iter()
+            .map(|tv| tv.name.clone())
+            .zip(self.gradients())
+            .collect()
+    }
+
+    // Takes a step of gradient descent with the given gradients, which are matched to the
+    // trainable variables by name. Variables without a gradient are treated as if their gradient
+    // were zero. The gradients are clipped and constraints are applied, just like in fit. Returns
+    // the global norm of the gradients before clipping.
+    pub fn apply_gradients(
+        &mut self,
+        gradients: &[(String, ndarray::ArrayD<f32>)],
+        learning_rate: f32,
+    ) -> Result<f32, Box<dyn Error>> {
+        let mut ordered: Vec<_> = self
             .trainable_variables
             .iter()
-            .map(|v| v.gradient_node_id)
+            .map(|tv| ndarray::Array::zeros(tv.value.shape()))
             .collect();
+        for (name, gradient) in gradients {
+            let i = match self
+                .trainable_variables
+                .iter()
+                .position(|tv| &tv.name == name)
+            {
+                Some(i) => i,
+                None => bail!("unknown trainable variable: {}", name),
+            };
+            if gradient.raw_dim() != ordered[i].raw_dim() {
+                bail!(
+                    "shape mismatch for {}: expected {:?}, got {:?}",
+                    name,
+                    ordered[i].shape(),
+                    gradient.shape()
+                );
+            }
+            ordered[i] = gradient.clone();
+        }
+        let gradient_norm = clipping::global_norm(&ordered);
+        self.update_variables(ordered, learning_rate);
+        Ok(gradient_norm)
+    }
+
+    // Computes the loss and gradients for a single sample and takes a step of gradient descent
+    // with them. Returns the loss from before the step.
+    pub fn train_step<S1, D1, S2, D2>(
+        &mut self,
+        input: ndarray::ArrayBase<S1, D1>,
+        target: ndarray::ArrayBase<S2, D2>,
+        learning_rate: f32,
+    ) -> f32
+    where
+        S1: ndarray::Data<Elem = f32>,
+        D1: ndarray::Dimension,
+        S2: ndarray::Data<Elem = f32>,
+        D2: ndarray::Dimension,
+    {
+        self.set_target(target, 1.0);
+        (*self.input).set(input);
+        let mut node_ids = vec![self.loss_node_id];
+        node_ids.extend(self.trainable_variables.iter().map(|v| v.gradient_node_id));
+        self.graph.eval_nodes(node_ids);
+        let loss = *self.graph.node_output(self.loss_node_id).first().unwrap();
+        self.update_variables_from_graph(learning_rate);
+        loss
+    }
+
+    // Computes the weighted loss of the model over the dataset. The regularization penalties are
+    // added to the reduced loss once, so they aren't included in the losses of individual samples
+    // returned for Reduction::None.
+    pub fn loss<D: Dataset + ?Sized>(
+        &mut self,
+        dataset: &mut D,
+        reduction: losses::Reduction,
+    ) -> Result<ndarray::ArrayD<f32>, Box<dyn Error>> {
+        let mut sample_losses = Vec::with_capacity(dataset.len());
+        for i in 0..dataset.len() {
+            self.set_sample(dataset, i)?;
+            self.graph.eval_nodes(vec![self.sample_loss_node_id]);
+            sample_losses.push(
+                *self
+                    .graph
+                    .node_output(self.sample_loss_node_id)
+                    .first()
+                    .unwrap(),
+            );
+        }
+        let mut loss = losses::reduce(&sample_losses, reduction);
+        match (reduction, self.regularization_loss_node_id) {
+            (losses::Reduction::None, _) | (_, None) => {}
+            (_, Some(id)) => {
+                self.graph.eval_nodes(vec![id]);
+                loss += *self.graph.node_output(id).first().unwrap();
+            }
+        }
+        Ok(loss)
+    }
+
+    // Evaluates the model over the dataset, returning the mean weighted loss as "loss" along with
+    // the result of each metric.
+    pub fn evaluate<D: Dataset + ?Sized>(
+        &mut self,
+        dataset: &mut D,
+        metrics: &mut [Box<dyn metrics::Metric>],
+    ) -> Result<metrics::Logs, Box<dyn Error>> {
+        for metric in metrics.iter_mut() {
+            metric.reset();
+        }
+        let mut loss = 0.0;
+        for i in 0..dataset.len() {
+            self.set_sample(dataset, i)?;
+            self.graph
+                .eval_nodes(vec![self.loss_node_id, self.output_node_id]);
+            loss += self.graph.node_output(self.loss_node_id).first().unwrap();
+            let output = self.graph.node_output(self.output_node_id).view();
+            for metric in metrics.iter_mut() {
+                metric.update(output.view(), dataset.target(i)?);
+            }
+        }
+        let mut logs = metrics::Logs::new();
+        logs.insert("loss".to_string(), loss / dataset.len().max(1) as f32);
+        for metric in metrics.iter() {
+            logs.insert(metric.name(), metric.result());
+        }
+        Ok(logs)
+    }
+
+    // Returns the learning rate of the most recent step taken by fit.
+    pub fn learning_rate(&self) -> f32 {
+        self.learning_rate
+    }
+
+    // Replaces the learning rate schedule with a constant learning rate for the rest of the current
+    // call to fit. This is intended to be used by callbacks.
+    pub fn set_learning_rate(&mut self, learning_rate: f32) {
+        self.learning_rate = learning_rate;
+        self.learning_rate_override = Some(learning_rate);
+    }
+
+    // Stops the current call to fit at the end of the epoch. This is intended to be used by
+    // callbacks.
+    pub fn stop_training(&mut self) {
+        self.stop_training = true;
+    }
+
+    // Returns a copy of the value of each of the model's variables, including non-trainable ones.
+    pub fn get_weights(&self) -> Vec<(String, ndarray::ArrayD<f32>)> {
+        self.variables
+            .iter()
+            .map(|v| (v.name.clone(), v.value.get()))
+            .collect()
+    }
+
+    // Sets the values of the model's variables by name. Each value must have its variable's shape.
+    pub fn set_weights(
+        &mut self,
+        weights: &[(String, ndarray::ArrayD<f32>)],
+    ) -> Result<(), Box<dyn Error>> {
+        for (name, value) in weights {
+            let variable = match self.variables.iter().find(|v| &v.name == name) {
+                Some(v) => v,
+                None => bail!("unknown variable: {}", name),
+            };
+            if variable.value.shape() != value.raw_dim() {
+                bail!(
+                    "shape mismatch for {}: expected {:?}, got {:?}",
+                    name,
+                    ndarray::Dimension::slice(&variable.value.shape()),
+                    value.shape()
+                );
+            }
+            variable.value.set(value.view());
+        }
+        Ok(())
+    }
+
+    // Writes the model's weights to a file. For each variable, this is its name, its shape, then
+    // its values, all little-endian and prefixed by lengths.
+    pub fn save_weights<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let weights = self.get_weights();
+        file.write_u32::<LittleEndian>(weights.len() as u32)?;
+        for (name, value) in weights {
+            file.write_u32::<LittleEndian>(name.len() as u32)?;
+            file.write_all(name.as_bytes())?;
+            file.write_u32::<LittleEndian>(value.ndim() as u32)?;
+            for &n in value.shape() {
+                file.write_u32::<LittleEndian>(n as u32)?;
+            }
+            for &v in value.iter() {
+                file.write_f32::<LittleEndian>(v)?;
+            }
+        }
+        file.flush()?;
+        Ok(())
+    }
+
+    // Reads weights written by save_weights.
+    pub fn load_weights<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut weights = Vec::new();
+        for _ in 0..file.read_u32::<LittleEndian>()? {
+            let mut name = vec![0; file.read_u32::<LittleEndian>()? as usize];
+            file.read_exact(&mut name)?;
+            let mut shape = Vec::new();
+            for _ in 0..file.read_u32::<LittleEndian>()? {
+                shape.push(file.read_u32::<LittleEndian>()? as usize);
+            }
+            let mut values = vec![0.0; shape.iter().product()];
+            file.read_f32_into::<LittleEndian>(&mut values)?;
+            weights.push((
+                String::from_utf8(name)?,
+                ndarray::Array::from_shape_vec(shape, values)?,
+            ));
+        }
+        self.set_weights(&weights)
+    }
+
+    // Trains the model using stochastic gradient descent. The learning rate schedule is evaluated
+    // for each step, counting from the start of the call. At the end of each epoch, the loss and
+    // metrics are computed for the training data and the validation data, if given. They're logged,
+    // passed to the callbacks, and recorded in the returned history.
+    pub fn fit<D: Dataset + ?Sized, S: schedules::LearningRateSchedule>(
+        &mut self,
+        dataset: &mut D,
+        learning_rate: S,
+        epochs: usize,
+        validation_data: Option<&mut dyn Dataset>,
+        callbacks: &mut [Box<dyn callbacks::Callback>],
+    ) -> Result<callbacks::History, Box<dyn Error>> {
+        // the metrics are taken so that they can be updated while the model is borrowed, and must
+        // be put back however training ends
+        let mut metrics = std::mem::take(&mut self.metrics);
+        self.learning_rate = learning_rate.learning_rate(0);
+        self.learning_rate_override = None;
+        self.stop_training = false;
+        let result = callbacks
+            .iter_mut()
+            .try_for_each(|callback| callback.on_train_begin(self))
+            .and_then(|_| {
+                let mut result = self.fit_epochs(
+                    dataset,
+                    learning_rate,
+                    epochs,
+                    validation_data,
+                    callbacks,
+                    &mut metrics,
+                );
+
+                // the callbacks can clean up even if training failed, but the first error wins
+                for callback in callbacks.iter_mut() {
+                    if let Err(e) = callback.on_train_end(self) {
+                        if result.is_ok() {
+                            result = Err(e);
+                        }
+                    }
+                }
+                result
+            });
+        self.metrics = metrics;
+        result
+    }
+
+    fn fit_epochs<D: Dataset + ?Sized, S: schedules::LearningRateSchedule>(
+        &mut self,
+        dataset: &mut D,
+        learning_rate: S,
+        epochs: usize,
+        mut validation_data: Option<&mut dyn Dataset>,
+        callbacks: &mut [Box<dyn callbacks::Callback>],
+        metrics: &mut [Box<dyn metrics::Metric>],
+    ) -> Result<callbacks::History, Box<dyn Error>> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut node_ids = vec![self.loss_node_id, self.output_node_id];
+        node_ids.extend(self.trainable_variables.iter().map(|v| v.gradient_node_id));
+        let mut history = callbacks::History::default();
+        let mut step = 0;
+        let mut accumulated_gradients: Vec<ndarray::ArrayD<f32>> = Vec::new();
+        let mut micro_batches = 0;
         for epoch in 0..epochs {
+            for callback in callbacks.iter_mut() {
+                callback.on_epoch_begin(self, epoch)?;
+            }
+            for metric in metrics.iter_mut() {
+                metric.reset();
+            }
+            let mut loss = 0.0;
             let mut samples: Vec<usize> = (0..dataset.len()).collect();
             samples.shuffle(&mut rng);
-            let mut step = 0;
-            for j in samples {
-                (*self.input).set(dataset.input(j)?);
-                (*self.target).set(dataset.target(j)?);
-                self.graph.eval_nodes(gradient_node_ids.clone());
-                for tv in self.trainable_variables.iter() {
-                    tv.value.set(
-                        tv.value.get()
-                            - self.graph.node_output(tv.gradient_node_id) * learning_rate,
-                    );
+            for (batch, &j) in samples.iter().enumerate() {
+                self.set_sample(dataset, j)?;
+                self.graph.eval_nodes(node_ids.clone());
+
+                // the metrics and loss are for the weights before this step
+                loss += self.graph.node_output(self.loss_node_id).first().unwrap();
+                let output = self.graph.node_output(self.output_node_id).view();
+                for metric in metrics.iter_mut() {
+                    metric.update(output.view(), dataset.target(j)?);
+                }
+
+                // without accumulation, the gradients are used straight from the graph
+                let accumulate = self.gradient_accumulation_steps > 1;
+                if accumulate {
+                    if accumulated_gradients.is_empty() {
+                        accumulated_gradients = self.gradients();
+                    } else {
+                        for (accumulated, tv) in accumulated_gradients
+                            .iter_mut()
+                            .zip(self.trainable_variables.iter())
+                        {
+                            *accumulated += self.graph.node_output(tv.gradient_node_id);
+                        }
+                    }
                 }
-                if step % 10000 == 0 {
-                    info!(
-                        "epoch {}, step {}; accuracy: {}",
-                        epoch,
-                        step,
-                        self.eval_accuracy(dataset)?
-                    );
+                micro_batches += 1;
+
+                // the gradient norm is only needed by callbacks
+                let mut gradient_norm = None;
+                if micro_batches == self.gradient_accumulation_steps || batch + 1 == samples.len() {
+                    self.learning_rate = self
+                        .learning_rate_override
+                        .unwrap_or_else(|| learning_rate.learning_rate(step));
+                    step += 1;
+                    if accumulate {
+                        let gradients: Vec<_> = std::mem::take(&mut accumulated_gradients)
+                            .into_iter()
+                            .map(|g| g / micro_batches as f32)
+                            .collect();
+                        if !callbacks.is_empty() {
+                            gradient_norm = Some(clipping::global_norm(&gradients));
+                        }
+                        self.update_variables(gradients, self.learning_rate);
+                    } else {
+                        if !callbacks.is_empty() {
+                            gradient_norm = Some(self.gradient_norm());
+                        }
+                        self.update_variables_from_graph(self.learning_rate);
+                    }
+                    micro_batches = 0;
+                }
+
+                if !callbacks.is_empty() {
+                    // the loss is averaged over the epoch so far, but the gradient norm is for
+                    // this step, before clipping. the metrics are left for the end of the epoch
+                    // since some, like AUC, are expensive to compute
+                    let mut logs = metrics::Logs::new();
+                    logs.insert("loss".to_string(), loss / (batch + 1) as f32);
+                    if let Some(gradient_norm) = gradient_norm {
+                        logs.insert("gradient_norm".to_string(), gradient_norm);
+                    }
+                    for callback in callbacks.iter_mut() {
+                        callback.on_batch_end(self, batch, &logs)?;
+                    }
+                }
+            }
+
+            let mut logs = metrics::Logs::new();
+            logs.insert("loss".to_string(), loss / dataset.len().max(1) as f32);
+            for metric in metrics.iter() {
+                logs.insert(metric.name(), metric.result());
+            }
+            if let Some(validation_data) = validation_data.as_mut() {
+                for (name, value) in self.evaluate(*validation_data, metrics)? {
+                    logs.insert(format!("val_{}", name), value);
                 }
-                step += 1
+            }
+            info!(
+                "epoch {}; {}",
+                epoch,
+                logs.iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            for callback in callbacks.iter_mut() {
+                callback.on_epoch_end(self, epoch, &logs)?;
+            }
+            history.epochs.push(logs);
+            if self.stop_training {
+                break;
             }
         }
-        Ok(())
+        Ok(history)
     }
 
     pub fn predict<S, D>(&mut self, input: ndarray::ArrayBase<S, D>) -> &ndarray::ArrayD<f32>
@@ -197,16 +880,353 @@ impl CompiledTrainingSequential {
         self.graph.eval_nodes(vec![self.output_node_id]);
         self.graph.node_output(self.output_node_id)
     }
+}
 
-    fn eval_accuracy<D: Dataset>(&mut self, dataset: &mut D) -> Result<f32, Box<dyn Error>> {
-        let mut samples: Vec<usize> = (0..dataset.len()).collect();
-        samples.shuffle(&mut rand::thread_rng());
-        let mut correct = 0;
-        for &j in samples.iter().take(std::cmp::max(samples.len(), 500)) {
-            if max_index(self.predict(dataset.input(j)?)) == max_index(&dataset.target(j)?) {
-                correct += 1
-            }
-        }
-        Ok(correct as f32 / dataset.len() as f32)
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{bias_model, BatchLogsRecorder, Bias, TestDataset};
+    use super::super::{layers, losses, metrics, regularizers, schedules};
+    use super::*;
+
+    #[test]
+    fn test_duplicate_variable_names() {
+        let mut model = Sequential::new(ndarray::Ix1(2));
+        model.add_layer(Bias {}).unwrap();
+        assert!(model
+            .compile_for_training(ndarray::Ix1(2), losses::categorical_cross_entropy)
+            .is_ok());
+
+        let mut model = Sequential::new(ndarray::Ix1(2));
+        model.add_layer(Bias {}).unwrap();
+        model.add_layer(Bias {}).unwrap();
+        assert!(model
+            .compile_for_training(ndarray::Ix1(2), losses::categorical_cross_entropy)
+            .is_err());
+
+        let mut model = Sequential::new(ndarray::Ix1(2));
+        model.add_layer(Bias {}).unwrap();
+        model.add_layer(Bias {}).unwrap();
+        assert!(model.compile_for_inference().is_err());
+    }
+
+    #[test]
+    fn test_weights() {
+        let mut dataset = TestDataset::new(&[[1.0, 0.0], [0.0, 1.0]]);
+        dataset.weights[1] = 0.0;
+        let mut model = bias_model();
+
+        assert_eq!(
+            model.loss(&mut dataset, losses::Reduction::None).unwrap(),
+            ndarray::arr1(&[0.5, 0.0]).into_dyn()
+        );
+
+        let mut class_weights = HashMap::new();
+        class_weights.insert(0, 3.0);
+        model.set_class_weights(class_weights);
+        assert_eq!(
+            model.loss(&mut dataset, losses::Reduction::Sum).unwrap(),
+            ndarray::arr0(1.5).into_dyn()
+        );
+        assert_eq!(
+            model.loss(&mut dataset, losses::Reduction::Mean).unwrap(),
+            ndarray::arr0(0.75).into_dyn()
+        );
+
+        // the second sample has no weight, so only the first should be learned
+        model.fit(&mut dataset, 0.1, 1, None, &mut []).unwrap();
+        assert_eq!(
+            *model.predict(ndarray::arr1(&[0.0, 0.0])),
+            ndarray::arr1(&[0.3, 0.0]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut dataset = TestDataset::new(&[[1.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+        let mut model = bias_model();
+
+        let mut metrics: Vec<Box<dyn metrics::Metric>> = vec![
+            Box::new(metrics::Accuracy::new()),
+            Box::new(metrics::MeanSquaredError::new()),
+        ];
+        model.fit(&mut dataset, 0.5, 1, None, &mut []).unwrap();
+        let logs = model.evaluate(&mut dataset, &mut metrics).unwrap();
+        assert_eq!(logs.len(), 3);
+        assert_eq!(logs["accuracy"], 2.0 / 3.0);
+        assert_eq!(logs["loss"], logs["mean_squared_error"]);
+
+        // validation results are only reported, so they shouldn't affect training
+        let mut validation_data = TestDataset::new(&[[0.0, 1.0]]);
+        let expected = model.predict(ndarray::arr1(&[0.0, 0.0])).clone();
+        model.set_metrics(metrics);
+        model
+            .fit(&mut dataset, 0.0, 1, Some(&mut validation_data), &mut [])
+            .unwrap();
+        assert_eq!(*model.predict(ndarray::arr1(&[0.0, 0.0])), expected);
+    }
+
+    #[test]
+    fn test_metrics_after_failed_fit() {
+        let mut dataset = TestDataset::new(&[[1.0, 0.0]]);
+        let mut model = bias_model();
+        model.set_metrics(vec![Box::new(metrics::Accuracy::new())]);
+
+        // there's no validation loss to monitor, so the first epoch fails
+        let mut callbacks: Vec<Box<dyn callbacks::Callback>> = vec![Box::new(
+            callbacks::EarlyStopping::new("val_loss", callbacks::Mode::Min, 1, false),
+        )];
+        assert!(model
+            .fit(&mut dataset, 0.1, 1, None, &mut callbacks)
+            .is_err());
+
+        let history = model.fit(&mut dataset, 0.1, 1, None, &mut []).unwrap();
+        assert_eq!(history.get("accuracy"), vec![1.0]);
+    }
+
+    #[test]
+    fn test_learning_rate_schedule() {
+        let mut dataset = TestDataset::new(&[[1.0, 0.0], [1.0, 0.0]]);
+        let mut model = bias_model();
+
+        // the schedule continues across epochs, so the four steps use 0.5, 0.25, 0.125, 0.0625
+        let schedule = schedules::StepDecay {
+            initial_learning_rate: 0.5,
+            factor: 0.5,
+            step_size: 1,
+        };
+        model.fit(&mut dataset, schedule, 2, None, &mut []).unwrap();
+        assert_eq!(model.learning_rate(), 0.0625);
+        assert_eq!(
+            *model.predict(ndarray::arr1(&[0.0, 0.0])),
+            ndarray::arr1(&[1.0 - 0.5 * 0.75 * 0.875 * 0.937_5, 0.0]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_custom_training_loop() {
+        let mut model = bias_model();
+        let input = ndarray::arr1(&[0.0, 0.0]);
+        let target = ndarray::arr1(&[1.0, 0.0]);
+
+        assert_eq!(model.compute_loss(input.view(), target.view()), 0.5);
+        let gradients = model.compute_gradients(input.view(), target.view());
+        assert_eq!(
+            gradients,
+            vec![("b".to_string(), ndarray::arr1(&[-1.0, 0.0]).into_dyn())]
+        );
+
+        // computing doesn't update the model, so we can e.g. accumulate gradients ourselves
+        let accumulated: Vec<_> = gradients
+            .into_iter()
+            .map(|(name, gradient)| (name, gradient * 2.0))
+            .collect();
+        assert_eq!(model.apply_gradients(&accumulated, 0.25).unwrap(), 2.0);
+        assert_eq!(
+            *model.predict(input.view()),
+            ndarray::arr1(&[0.5, 0.0]).into_dyn()
+        );
+
+        assert_eq!(model.train_step(input.view(), target.view(), 1.0), 0.125);
+        assert_eq!(model.compute_loss(input.view(), target.view()), 0.0);
+
+        let unknown = vec![("x".to_string(), ndarray::arr1(&[1.0, 0.0]).into_dyn())];
+        assert!(model.apply_gradients(&unknown, 1.0).is_err());
+        let misshapen = vec![("b".to_string(), ndarray::arr1(&[1.0]).into_dyn())];
+        assert!(model.apply_gradients(&misshapen, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_gradient_accumulation() {
+        let mut dataset = TestDataset::new(&[[1.0, 0.0], [0.0, 1.0]]);
+        let mut model = bias_model();
+        assert!(model.set_gradient_accumulation_steps(0).is_err());
+
+        // both samples are seen from the same weights, so their order doesn't matter
+        model.set_gradient_accumulation_steps(2).unwrap();
+        model.fit(&mut dataset, 1.0, 1, None, &mut []).unwrap();
+        assert_eq!(
+            *model.predict(ndarray::arr1(&[0.0, 0.0])),
+            ndarray::arr1(&[0.5, 0.5]).into_dyn()
+        );
+
+        // the last micro-batch of each epoch gets its own step, so there are two steps per epoch,
+        // and the second only averages over one sample
+        let mut dataset = TestDataset::new(&[[1.0, 0.0]; 3]);
+        let mut model = bias_model();
+        model.set_gradient_accumulation_steps(2).unwrap();
+        let schedule = schedules::StepDecay {
+            initial_learning_rate: 0.5,
+            factor: 0.5,
+            step_size: 1,
+        };
+        let logs = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut callbacks: Vec<Box<dyn callbacks::Callback>> =
+            vec![Box::new(BatchLogsRecorder { logs: logs.clone() })];
+        model
+            .fit(&mut dataset, schedule, 2, None, &mut callbacks)
+            .unwrap();
+        assert_eq!(model.learning_rate(), 0.0625);
+
+        // the steps move the bias by 0.5 * 1.0, 0.25 * 0.5, 0.125 * 0.375, and 0.0625 * 0.328125
+        assert_eq!(
+            *model.predict(ndarray::arr1(&[0.0, 0.0])),
+            ndarray::arr1(&[0.692_382_8, 0.0]).into_dyn()
+        );
+
+        // only the batches that complete a step report the norm of its gradients
+        let norms: Vec<_> = logs
+            .borrow()
+            .iter()
+            .map(|l| l.get("gradient_norm").cloned())
+            .collect();
+        assert_eq!(
+            norms,
+            vec![
+                None,
+                Some(1.0),
+                Some(0.5),
+                None,
+                Some(0.375),
+                Some(0.328_125)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_regularizers_and_constraints() {
+        let mut dataset = TestDataset {
+            inputs: vec![ndarray::arr1(&[1.0]).into_dyn(); 2],
+            targets: vec![ndarray::arr1(&[0.0, 0.0]).into_dyn(); 2],
+            weights: vec![0.0; 2],
+        };
+        let mut model = Sequential::new(ndarray::Ix1(1));
+        model
+            .add_layer(layers::Dense {
+                activation: |x| x,
+                kernel_initializer: initializers::copy(ndarray::arr2(&[[-1.0], [2.0]]).into_dyn()),
+                kernel_regularizer: Some(regularizers::l2(0.5)),
+                bias_regularizer: None,
+                kernel_constraint: Some(constraints::non_neg()),
+                output_size: 2,
+            })
+            .unwrap();
+        let mut model = model
+            .compile_for_training(ndarray::Ix1(2), losses::mean_squared_error)
+            .unwrap();
+
+        // the samples have no weight, so the loss is just the penalty, which is counted once
+        assert_eq!(
+            model.loss(&mut dataset, losses::Reduction::Sum).unwrap(),
+            ndarray::arr0(2.5).into_dyn()
+        );
+        assert_eq!(
+            model.loss(&mut dataset, losses::Reduction::Mean).unwrap(),
+            ndarray::arr0(2.5).into_dyn()
+        );
+        assert_eq!(
+            model.loss(&mut dataset, losses::Reduction::None).unwrap(),
+            ndarray::arr1(&[0.0, 0.0]).into_dyn()
+        );
+
+        // the penalty shrinks the kernel, then the constraint clips the negative weight
+        dataset.inputs.pop();
+        dataset.targets.pop();
+        dataset.weights.pop();
+        model.fit(&mut dataset, 0.1, 1, None, &mut []).unwrap();
+        assert_eq!(
+            *model.predict(ndarray::arr1(&[1.0])),
+            ndarray::arr1(&[0.0, 1.8]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_frozen() {
+        let mut dataset = TestDataset {
+            inputs: vec![ndarray::arr1(&[1.0]).into_dyn()],
+            targets: vec![ndarray::arr1(&[0.0]).into_dyn()],
+            weights: vec![1.0],
+        };
+        let mut model = Sequential::new(ndarray::Ix1(1));
+        model
+            .add_layer(layers::Frozen {
+                layer: Box::new(layers::Dense {
+                    activation: |x| x,
+                    kernel_initializer: initializers::ones,
+                    kernel_regularizer: None,
+                    bias_regularizer: None,
+                    kernel_constraint: None,
+                    output_size: 1,
+                }),
+            })
+            .unwrap();
+        model.add_layer(Bias {}).unwrap();
+        let mut model = model
+            .compile_for_training(ndarray::Ix1(1), losses::mean_squared_error)
+            .unwrap();
+
+        // only the bias should get a gradient node and be updated
+        assert_eq!(model.trainable_variables.len(), 1);
+        model.fit(&mut dataset, 0.25, 1, None, &mut []).unwrap();
+        assert_eq!(
+            *model.predict(ndarray::arr1(&[2.0])),
+            ndarray::arr1(&[1.5]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_named_layers() {
+        let mut model = Sequential::new(ndarray::Ix1(2));
+        let dense = layers::Dense {
+            activation: |x| x,
+            kernel_initializer: initializers::zeros,
+            kernel_regularizer: None,
+            bias_regularizer: None,
+            kernel_constraint: None,
+            output_size: 1,
+        };
+        model.add_named_layer("head", dense).unwrap();
+        assert!(model.add_named_layer("head", Bias {}).is_err());
+        assert!(model.add_layer(Bias {}).is_ok());
+
+        // the layer name is used as the namespace for its variables
+        let names: Vec<_> = model.layers[0]
+            .instance
+            .variables()
+            .iter()
+            .map(|v| v.name.clone())
+            .collect();
+        assert!(names.iter().all(|name| name.starts_with("head.")));
+        assert_eq!(model.layers[1].name, "l1");
+    }
+
+    #[test]
+    fn test_summary() {
+        let dense = |output_size| layers::Dense {
+            activation: |x| x,
+            kernel_initializer: initializers::zeros,
+            kernel_regularizer: None,
+            bias_regularizer: None,
+            kernel_constraint: None,
+            output_size,
+        };
+        let mut model = Sequential::new(ndarray::Ix1(100));
+        model.add_named_layer("hidden", dense(20)).unwrap();
+        model
+            .add_layer(layers::Frozen {
+                layer: Box::new(dense(10)),
+            })
+            .unwrap();
+        assert_eq!(
+            model.summary(),
+            "Layer (type)    Output Shape  Param #
+=====================================
+hidden (Dense)  [20]            2,020
+l1 (Frozen)     [10]              210
+=====================================
+Total params: 2,230 (8.71 KB)
+Trainable params: 2,020
+Non-trainable params: 210
+"
+        );
     }
 }
@@ -0,0 +1,80 @@
+use std::rc::Rc;
+
+use super::algebra;
+
+use ndarray::Dimension;
+
+// A regularizer returns a penalty for a variable that's added to the training loss.
+pub type Regularizer = Rc<dyn Fn(algebra::Expr) -> algebra::Expr>;
+
+pub fn l1(factor: f32) -> Regularizer {
+    Rc::new(move |v: algebra::Expr| factor * v.abs().sum())
+}
+
+pub fn l2(factor: f32) -> Regularizer {
+    Rc::new(move |v: algebra::Expr| factor * v.square().sum())
+}
+
+pub fn l1_l2(l1: f32, l2: f32) -> Regularizer {
+    Rc::new(move |v: algebra::Expr| l1 * v.abs().sum() + l2 * v.square().sum())
+}
+
+// Penalizes the squared distance of the kernel's gram matrix from the identity. The kernel is
+// treated as a matrix whose columns are its last axis, and the gram matrix is taken over whichever
+// of the rows or columns there are fewer of, since only those can be orthogonal. For Dense, that's
+// usually the output units.
+pub fn orthogonal(factor: f32) -> Regularizer {
+    Rc::new(move |v: algebra::Expr| {
+        let shape = v.shape();
+        let cols = shape.slice().last().cloned().unwrap_or(1);
+        let rows = shape.size() / cols;
+        let w = v.reshape(ndarray::Ix2(rows, cols));
+        let (gram, n) = if rows <= cols {
+            (algebra::matmul(w.clone(), w.transpose()), rows)
+        } else {
+            (algebra::matmul(w.transpose(), w), cols)
+        };
+        factor
+            * (gram - algebra::expr(ndarray::Array2::<f32>::eye(n)))
+                .square()
+                .sum()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test() {
+        let w = algebra::v(
+            "w",
+            Rc::new(algebra::VariableValue::new(ndarray::arr2(&[
+                [1.0, -2.0],
+                [0.0, 3.0],
+            ]))),
+        );
+        assert_eq!(l1(0.1)(w.clone()).eval(), ndarray::arr0(0.6).into_dyn());
+        assert_eq!(l2(0.1)(w.clone()).eval(), ndarray::arr0(1.4).into_dyn());
+        assert_eq!(
+            l1_l2(0.1, 0.1)(w.clone()).eval(),
+            ndarray::arr0(2.0).into_dyn()
+        );
+        assert_eq!(
+            l2(0.5)(w.clone()).gradient("w").eval(),
+            ndarray::arr2(&[[1.0, -2.0], [0.0, 3.0]]).into_dyn()
+        );
+
+        // the gram matrix is [[5, -6], [-6, 9]]
+        assert_eq!(
+            orthogonal(0.5)(w).eval(),
+            ndarray::arr0(0.5 * (16.0 + 36.0 + 36.0 + 64.0)).into_dyn()
+        );
+        let identity = algebra::expr(ndarray::Array2::<f32>::eye(3).slice(s![.., ..2]).to_owned());
+        assert_eq!(
+            orthogonal(1.0)(identity).eval(),
+            ndarray::arr0(0.0).into_dyn()
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use std::f32::consts::PI;
+
+// A learning rate schedule determines the learning rate for each optimizer step, starting at 0.
+pub trait LearningRateSchedule {
+    fn learning_rate(&self, step: usize) -> f32;
+}
+
+// A constant learning rate.
+impl LearningRateSchedule for f32 {
+    fn learning_rate(&self, _step: usize) -> f32 {
+        *self
+    }
+}
+
+// Multiplies the learning rate by a factor every step_size steps.
+pub struct StepDecay {
+    pub initial_learning_rate: f32,
+    pub factor: f32,
+    pub step_size: usize,
+}
+
+impl LearningRateSchedule for StepDecay {
+    fn learning_rate(&self, step: usize) -> f32 {
+        assert!(self.step_size > 0, "step_size must be positive");
+        self.initial_learning_rate * self.factor.powi((step / self.step_size) as i32)
+    }
+}
+
+// Multiplies the learning rate by decay_rate every decay_steps steps, either continuously or, if
+// staircase is true, in discrete intervals.
+pub struct ExponentialDecay {
+    pub initial_learning_rate: f32,
+    pub decay_rate: f32,
+    pub decay_steps: usize,
+    pub staircase: bool,
+}
+
+impl LearningRateSchedule for ExponentialDecay {
+    fn learning_rate(&self, step: usize) -> f32 {
+        assert!(self.decay_steps > 0, "decay_steps must be positive");
+        let mut p = step as f32 / self.decay_steps as f32;
+        if self.staircase {
+            p = p.floor();
+        }
+        self.initial_learning_rate * self.decay_rate.powf(p)
+    }
+}
+
+// Decays the learning rate to end_learning_rate over decay_steps steps along a polynomial, then
+// holds it there.
+pub struct PolynomialDecay {
+    pub initial_learning_rate: f32,
+    pub end_learning_rate: f32,
+    pub decay_steps: usize,
+    pub power: f32,
+}
+
+impl LearningRateSchedule for PolynomialDecay {
+    fn learning_rate(&self, step: usize) -> f32 {
+        assert!(self.decay_steps > 0, "decay_steps must be positive");
+        let p = step.min(self.decay_steps) as f32 / self.decay_steps as f32;
+        (self.initial_learning_rate - self.end_learning_rate) * (1.0 - p).powf(self.power)
+            + self.end_learning_rate
+    }
+}
+
+// Anneals from max to min along half of a cosine as fraction goes from 0 to 1.
+fn cosine_annealing(max: f32, min: f32, fraction: f32) -> f32 {
+    min + (max - min) * 0.5 * (1.0 + (PI * fraction).cos())
+}
+
+// Cosine annealing with warm restarts (SGDR). The learning rate decays from the initial learning
+// rate to alpha times it over first_decay_steps steps, then restarts. Each period is t_mul times as
+// long as the last, and each restart is m_mul times as high. Periods can't get shorter, so t_mul
+// must be at least 1.
+pub struct CosineDecayRestarts {
+    pub initial_learning_rate: f32,
+    pub first_decay_steps: usize,
+    pub t_mul: f32,
+    pub m_mul: f32,
+    pub alpha: f32,
+}
+
+impl LearningRateSchedule for CosineDecayRestarts {
+    fn learning_rate(&self, step: usize) -> f32 {
+        assert!(
+            self.first_decay_steps > 0,
+            "first_decay_steps must be positive"
+        );
+        assert!(self.t_mul >= 1.0, "t_mul must be at least 1");
+        if self.t_mul == 1.0 {
+            let restarts = step / self.first_decay_steps;
+            let max = self.initial_learning_rate * self.m_mul.powf(restarts as f32);
+            let fraction = (step % self.first_decay_steps) as f32 / self.first_decay_steps as f32;
+            return cosine_annealing(max, max * self.alpha, fraction);
+        }
+
+        // find the period the step falls in by walking through them, which avoids the rounding
+        // errors of computing it with logarithms
+        let step = step as f64;
+        let mut period_start = 0.0;
+        let mut period = self.first_decay_steps as f64;
+        let mut max = self.initial_learning_rate;
+        while step >= period_start + period {
+            period_start += period;
+            period *= self.t_mul as f64;
+            max *= self.m_mul;
+        }
+        let fraction = ((step - period_start) / period) as f32;
+        cosine_annealing(max, max * self.alpha, fraction)
+    }
+}
+
+// Increases the learning rate linearly from 0 over warmup_steps steps, then follows another
+// schedule, which starts from its first step.
+pub struct LinearWarmup<S: LearningRateSchedule> {
+    pub warmup_steps: usize,
+    pub schedule: S,
+}
+
+impl<S: LearningRateSchedule> LearningRateSchedule for LinearWarmup<S> {
+    fn learning_rate(&self, step: usize) -> f32 {
+        if step < self.warmup_steps {
+            self.schedule.learning_rate(0) * step as f32 / self.warmup_steps as f32
+        } else {
+            self.schedule.learning_rate(step - self.warmup_steps)
+        }
+    }
+}
+
+// The one-cycle policy. The learning rate anneals from max_learning_rate / div_factor up to
+// max_learning_rate over the first pct_start of the total steps, then anneals down to
+// max_learning_rate / div_factor / final_div_factor at the last step.
+pub struct OneCycle {
+    pub max_learning_rate: f32,
+    pub total_steps: usize,
+    pub pct_start: f32,
+    pub div_factor: f32,
+    pub final_div_factor: f32,
+}
+
+impl LearningRateSchedule for OneCycle {
+    fn learning_rate(&self, step: usize) -> f32 {
+        let initial_learning_rate = self.max_learning_rate / self.div_factor;
+        let min_learning_rate = initial_learning_rate / self.final_div_factor;
+        let step = step as f32;
+        let peak_step = self.pct_start * self.total_steps as f32 - 1.0;
+        let last_step = self.total_steps as f32 - 1.0;
+        if step < peak_step {
+            cosine_annealing(
+                self.max_learning_rate,
+                initial_learning_rate,
+                1.0 - step / peak_step,
+            )
+        } else {
+            cosine_annealing(
+                self.max_learning_rate,
+                min_learning_rate,
+                ((step - peak_step) / (last_step - peak_step).max(1.0)).min(1.0),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    fn check<S: LearningRateSchedule>(schedule: S, expected: &[f32]) {
+        let actual: Vec<_> = (0..expected.len())
+            .map(|step| schedule.learning_rate(step))
+            .collect();
+        assert!(
+            ndarray::arr1(&actual).all_close(&ndarray::arr1(expected), 1e-6),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_step_decay() {
+        check(
+            StepDecay {
+                initial_learning_rate: 1.0,
+                factor: 0.5,
+                step_size: 2,
+            },
+            &[1.0, 1.0, 0.5, 0.5, 0.25],
+        );
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        check(
+            ExponentialDecay {
+                initial_learning_rate: 1.0,
+                decay_rate: 0.5,
+                decay_steps: 2,
+                staircase: false,
+            },
+            &[1.0, FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2 / 2.0],
+        );
+        check(
+            ExponentialDecay {
+                initial_learning_rate: 1.0,
+                decay_rate: 0.5,
+                decay_steps: 2,
+                staircase: true,
+            },
+            &[1.0, 1.0, 0.5, 0.5],
+        );
+    }
+
+    #[test]
+    fn test_polynomial_decay() {
+        check(
+            PolynomialDecay {
+                initial_learning_rate: 1.0,
+                end_learning_rate: 0.1,
+                decay_steps: 4,
+                power: 1.0,
+            },
+            &[1.0, 0.775, 0.55, 0.325, 0.1, 0.1],
+        );
+        check(
+            PolynomialDecay {
+                initial_learning_rate: 1.0,
+                end_learning_rate: 0.0,
+                decay_steps: 2,
+                power: 2.0,
+            },
+            &[1.0, 0.25, 0.0],
+        );
+    }
+
+    #[test]
+    fn test_cosine_decay_restarts() {
+        check(
+            CosineDecayRestarts {
+                initial_learning_rate: 1.0,
+                first_decay_steps: 2,
+                t_mul: 2.0,
+                m_mul: 0.5,
+                alpha: 0.0,
+            },
+            &[1.0, 0.5, 0.5, 0.426_776_7, 0.25, 0.073_223_3, 0.25],
+        );
+        check(
+            CosineDecayRestarts {
+                initial_learning_rate: 1.0,
+                first_decay_steps: 2,
+                t_mul: 1.0,
+                m_mul: 1.0,
+                alpha: 0.5,
+            },
+            &[1.0, 0.75, 1.0, 0.75],
+        );
+
+        // without growing periods, the period is found directly
+        let schedule = CosineDecayRestarts {
+            initial_learning_rate: 1.0,
+            first_decay_steps: 2,
+            t_mul: 1.0,
+            m_mul: 0.5,
+            alpha: 0.5,
+        };
+        assert_eq!(schedule.learning_rate(5), 0.25 * 0.75);
+        assert_eq!(schedule.learning_rate(1_000_000_000), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "first_decay_steps must be positive")]
+    fn test_cosine_decay_restarts_without_steps() {
+        CosineDecayRestarts {
+            initial_learning_rate: 1.0,
+            first_decay_steps: 0,
+            t_mul: 2.0,
+            m_mul: 1.0,
+            alpha: 0.0,
+        }
+        .learning_rate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "t_mul must be at least 1")]
+    fn test_cosine_decay_restarts_with_shrinking_periods() {
+        CosineDecayRestarts {
+            initial_learning_rate: 1.0,
+            first_decay_steps: 2,
+            t_mul: 0.5,
+            m_mul: 1.0,
+            alpha: 0.0,
+        }
+        .learning_rate(10);
+    }
+
+    #[test]
+    #[should_panic(expected = "step_size must be positive")]
+    fn test_step_decay_without_steps() {
+        StepDecay {
+            initial_learning_rate: 1.0,
+            factor: 0.5,
+            step_size: 0,
+        }
+        .learning_rate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "decay_steps must be positive")]
+    fn test_exponential_decay_without_steps() {
+        ExponentialDecay {
+            initial_learning_rate: 1.0,
+            decay_rate: 0.5,
+            decay_steps: 0,
+            staircase: false,
+        }
+        .learning_rate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "decay_steps must be positive")]
+    fn test_polynomial_decay_without_steps() {
+        PolynomialDecay {
+            initial_learning_rate: 1.0,
+            end_learning_rate: 0.0,
+            decay_steps: 0,
+            power: 1.0,
+        }
+        .learning_rate(0);
+    }
+
+    #[test]
+    fn test_linear_warmup() {
+        check(
+            LinearWarmup {
+                warmup_steps: 2,
+                schedule: StepDecay {
+                    initial_learning_rate: 1.0,
+                    factor: 0.5,
+                    step_size: 1,
+                },
+            },
+            &[0.0, 0.5, 1.0, 0.5, 0.25],
+        );
+    }
+
+    #[test]
+    fn test_one_cycle() {
+        check(
+            OneCycle {
+                max_learning_rate: 1.0,
+                total_steps: 5,
+                pct_start: 0.4,
+                div_factor: 10.0,
+                final_div_factor: 10.0,
+            },
+            &[0.1, 1.0, 0.7525, 0.2575, 0.01],
+        );
+    }
+}
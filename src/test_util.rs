@@ -0,0 +1,109 @@
+// Fixtures shared by the tests of multiple modules.
+use std::error::Error;
+use std::rc::Rc;
+
+use super::{
+    algebra, callbacks, losses, metrics, models, Dataset, Layer, LayerInstance, LayerVariable,
+};
+
+// Bias adds a variable that's always named "b", regardless of the namespace it's given.
+pub(crate) struct Bias {}
+
+struct BiasInstance {
+    variables: Vec<LayerVariable>,
+}
+
+impl LayerInstance for BiasInstance {
+    fn expression(&self, input: algebra::Expr) -> algebra::Expr {
+        let b = &self.variables[0];
+        input + algebra::v(b.name.clone(), b.value.clone())
+    }
+
+    fn variables(&self) -> &[LayerVariable] {
+        self.variables.as_slice()
+    }
+}
+
+impl Layer for Bias {
+    fn init(
+        self: Box<Self>,
+        _namespace: &str,
+        input_shape: &ndarray::IxDyn,
+    ) -> Box<dyn LayerInstance> {
+        Box::new(BiasInstance {
+            variables: vec![LayerVariable {
+                name: "b".to_string(),
+                value: Rc::new(algebra::VariableValue::new(ndarray::Array::zeros(
+                    input_shape.clone(),
+                ))),
+                constraint: None,
+                trainable: true,
+            }],
+        })
+    }
+}
+
+// Returns a model whose output for a zero input is just its bias, which starts at zero. It's
+// trained with the mean squared error.
+pub(crate) fn bias_model() -> models::CompiledTrainingSequential {
+    let mut model = models::Sequential::new(ndarray::Ix1(2));
+    model.add_layer(Bias {}).unwrap();
+    model
+        .compile_for_training(ndarray::Ix1(2), losses::mean_squared_error)
+        .unwrap()
+}
+
+pub(crate) struct TestDataset {
+    pub inputs: Vec<ndarray::ArrayD<f32>>,
+    pub targets: Vec<ndarray::ArrayD<f32>>,
+    pub weights: Vec<f32>,
+}
+
+impl TestDataset {
+    // Returns a dataset for bias_model with zero inputs, the given targets, and weights of one.
+    pub fn new(targets: &[[f32; 2]]) -> TestDataset {
+        TestDataset {
+            inputs: vec![ndarray::Array::zeros(ndarray::IxDyn(&[2])); targets.len()],
+            targets: targets
+                .iter()
+                .map(|t| ndarray::arr1(t).into_dyn())
+                .collect(),
+            weights: vec![1.0; targets.len()],
+        }
+    }
+}
+
+impl Dataset for TestDataset {
+    fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    fn input(&mut self, i: usize) -> Result<ndarray::ArrayViewD<'_, f32>, Box<dyn Error>> {
+        Ok(self.inputs[i].view())
+    }
+
+    fn target(&mut self, i: usize) -> Result<ndarray::ArrayViewD<'_, f32>, Box<dyn Error>> {
+        Ok(self.targets[i].view())
+    }
+
+    fn sample_weight(&mut self, i: usize) -> Result<f32, Box<dyn Error>> {
+        Ok(self.weights[i])
+    }
+}
+
+// Records the logs of each batch passed to it by fit.
+pub(crate) struct BatchLogsRecorder {
+    pub logs: Rc<std::cell::RefCell<Vec<metrics::Logs>>>,
+}
+
+impl callbacks::Callback for BatchLogsRecorder {
+    fn on_batch_end(
+        &mut self,
+        _model: &mut models::CompiledTrainingSequential,
+        _batch: usize,
+        logs: &metrics::Logs,
+    ) -> Result<(), Box<dyn Error>> {
+        self.logs.borrow_mut().push(logs.clone());
+        Ok(())
+    }
+}